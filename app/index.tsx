@@ -8,7 +8,7 @@ import { Menu, MenuBar, MenuButton } from "./menu";
 import { Tab, TabButton } from "./tab";
 import SeqTab from "./seq_view";
 import * as utils from "./utils";
-import { parse_bin, parse_gb, Seq } from "./worker_comms/worker_shims";
+import { parse_any, parse_bin, Seq } from "./worker_comms/worker_shims";
 import { standardTemplates } from "../templates";
 import { LogMessage } from "../worker/shared";
 import LogTab from "./log_view";
@@ -23,6 +23,21 @@ import { showWelcome, useFileNames } from "./config";
 import { readFileBinary, fetchBinary, removeGbExt } from "./utils/io";
 import SettingsTab from "./settings_view";
 
+// Parse a file in any supported format
+async function parseFile(data: ArrayBuffer): Promise<Seq[]> {
+  const parsed = await parse_any(data);
+  const seqs = await parsed.get_seqs();
+  const errors = await parsed.get_errors();
+  parsed.free();
+  if (errors.length) {
+    alert(
+      "Some files couldn't be imported:\n" +
+        errors.map(e => `${e.entry}: ${e.message}`).join("\n")
+    );
+  }
+  return seqs;
+}
+
 const StandardTemplates = React.memo(() => {
   return (
     <>
@@ -132,7 +147,7 @@ class App extends React.PureComponent<{}, State> {
       (async () => {
         try {
           const data = await readFileBinary(f);
-          const seqs = await parse_gb(data);
+          const seqs = await parseFile(data);
           if (seqs.length === 1 && useFileNames.val) {
             const seq = await seqs[0].set_name(removeGbExt(f.name));
             await this.saveFragment(seq);
@@ -151,7 +166,7 @@ class App extends React.PureComponent<{}, State> {
     try {
       for (const f of files) {
         const ab = await readFileBinary(f);
-        const seqs: Seq[] = await parse_gb(ab);
+        const seqs: Seq[] = await parseFile(ab);
         if (seqs.length === 0) {
           throw new Error(`File: '${f.name}' contains no sequences`);
         }
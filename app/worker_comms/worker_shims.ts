@@ -56,14 +56,121 @@ export function parse_gb(arg0: ArrayBuffer): Promise<Seq[]> {
   return workerCall("parse_gb", "obj_array", [arg0], ["binary"], [arg0]);
 }
 
+export function parse_gb_lenient(
+  arg0: ArrayBuffer
+): Promise<LenientParseResult> {
+  return workerCall("parse_gb_lenient", "obj", [arg0], ["binary"], [arg0]);
+}
+
+export interface ParseWarning {
+  /** null for lines outside of any record */
+  record: number | null;
+  line: number;
+  message: string;
+}
+
+export class LenientParseResult extends RemoteObj {
+  get_seqs(): Promise<Seq[]> {
+    return this.callMethod("get_seqs", "obj_array", [], [], []);
+  }
+  get_warnings(): Promise<ParseWarning[]> {
+    return this.callMethod("get_warnings", "val", [], [], []);
+  }
+}
+
 export function parse_fasta(arg0: ArrayBuffer): Promise<Seq[]> {
   return workerCall("parse_fasta", "obj_array", [arg0], ["binary"], [arg0]);
 }
 
+export interface FastaValidationSettings {
+  invalid: "reject" | "strip" | "convertToN";
+  gaps: "reject" | "strip" | "convertToN";
+}
+
+export interface FastaIssue {
+  record: number;
+  id: string;
+  position: number;
+  character: string;
+  kind: "invalid" | "gap";
+}
+
+export function validate_fasta(
+  arg0: ArrayBuffer,
+  arg1?: FastaValidationSettings
+): Promise<FastaValidation> {
+  return workerCall(
+    "validate_fasta",
+    "obj",
+    [arg0, arg1],
+    ["binary", "val"],
+    [arg0]
+  );
+}
+
+export class FastaValidation extends RemoteObj {
+  get_seqs(): Promise<Seq[]> {
+    return this.callMethod("get_seqs", "obj_array", [], [], []);
+  }
+  get_issues(): Promise<FastaIssue[]> {
+    return this.callMethod("get_issues", "val", [], [], []);
+  }
+  get_total_issues(): Promise<number> {
+    return this.callMethod("get_total_issues", "val", [], [], []);
+  }
+  get_rejected(): Promise<string[]> {
+    return this.callMethod("get_rejected", "val", [], [], []);
+  }
+}
+
+export function parse_embl(arg0: ArrayBuffer): Promise<Seq[]> {
+  return workerCall("parse_embl", "obj_array", [arg0], ["binary"], [arg0]);
+}
+
+export function parse_json(arg0: ArrayBuffer): Promise<Seq[]> {
+  return workerCall("parse_json", "obj_array", [arg0], ["binary"], [arg0]);
+}
+
+export function parse_snapgene(arg0: ArrayBuffer): Promise<Seq> {
+  return workerCall("parse_snapgene", "obj", [arg0], ["binary"], [arg0]);
+}
+
 export function parse_bin(arg0: ArrayBuffer): Promise<Seq> {
   return workerCall("parse_bin", "obj", [arg0], ["binary"], [arg0]);
 }
 
+export function parse_any(arg0: ArrayBuffer): Promise<ParsedFile> {
+  return workerCall("parse_any", "obj", [arg0], ["binary"], [arg0]);
+}
+
+/** Gzipped files are reported as the format of their contents */
+export type Format =
+  | "genbank"
+  | "fasta"
+  | "embl"
+  | "json"
+  | "snapgene"
+  | "bin"
+  | "project"
+  | "zip";
+
+export interface EntryError {
+  entry: string;
+  message: string;
+}
+
+export class ParsedFile extends RemoteObj {
+  get_format(): Promise<Format> {
+    return this.callMethod("get_format", "val", [], [], []);
+  }
+  get_seqs(): Promise<Seq[]> {
+    return this.callMethod("get_seqs", "obj_array", [], [], []);
+  }
+  get_errors(): Promise<EntryError[]> {
+    return this.callMethod("get_errors", "val", [], [], []);
+  }
+}
+
 export interface Metadata {
   name: string;
   len: number;
@@ -141,6 +248,24 @@ export class AssemblyResult extends RemoteObj {
       []
     );
   }
+  to_sbol_linear(arg0: number, arg1: string): Promise<string> {
+    return this.callMethod(
+      "to_sbol_linear",
+      "val",
+      [arg0, arg1],
+      ["val", "val"],
+      []
+    );
+  }
+  to_sbol_circular(arg0: number, arg1: string): Promise<string> {
+    return this.callMethod(
+      "to_sbol_circular",
+      "val",
+      [arg0, arg1],
+      ["val", "val"],
+      []
+    );
+  }
 }
 
 export function newAssembly(): Promise<Assembly> {
@@ -159,12 +284,232 @@ export class Assembly extends RemoteObj {
   }
 }
 
+export function newSeqList(): Promise<SeqList> {
+  return workerRequest({ command: "new", className: "SeqList", args: [] });
+}
+
+export interface ConcatSettings {
+  reverse?: boolean[];
+  circular?: boolean;
+  junctions?: boolean;
+}
+
+export class SeqList extends RemoteObj {
+  push(arg0: Seq): Promise<void> {
+    return this.callMethod("push", "val", [arg0], ["obj"], []);
+  }
+  to_fasta(arg0: number, arg1?: string): Promise<Uint8Array> {
+    return this.callMethod("to_fasta", "binary", [arg0, arg1], ["val", "val"], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+  to_json(): Promise<string> {
+    return this.callMethod("to_json", "val", [], [], []);
+  }
+  concatenate(arg0: ConcatSettings): Promise<Seq> {
+    return this.callMethod("concatenate", "obj", [arg0], ["val"], []);
+  }
+  to_gb(): Promise<Uint8Array> {
+    return this.callMethod("to_gb", "binary", [], [], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+}
+
+export function newProject(): Promise<Project> {
+  return workerRequest({ command: "new", className: "Project", args: [] });
+}
+
+export function parse_project(arg0: ArrayBuffer): Promise<Project> {
+  return workerCall("parse_project", "obj", [arg0], ["binary"], [arg0]);
+}
+
+export interface PcrSetup {
+  seq: number;
+  settings: PcrSettings;
+}
+
+export interface AssemblySetup {
+  seqs: number[];
+  settings: { limit: number };
+}
+
+export class Project extends RemoteObj {
+  set_name(arg0?: string): Promise<void> {
+    return this.callMethod("set_name", "val", [arg0], ["val"], []);
+  }
+  get_name(): Promise<string | undefined> {
+    return this.callMethod("get_name", "val", [], [], []);
+  }
+  push_seq(arg0: Seq): Promise<number> {
+    return this.callMethod("push_seq", "val", [arg0], ["obj"], []);
+  }
+  get_seqs(): Promise<Seq[]> {
+    return this.callMethod("get_seqs", "obj_array", [], [], []);
+  }
+  set_primers(arg0: any[]): Promise<void> {
+    return this.callMethod("set_primers", "val", [arg0], ["val"], []);
+  }
+  get_primers(): Promise<any[]> {
+    return this.callMethod("get_primers", "val", [], [], []);
+  }
+  push_pcr(arg0: number, arg1: PcrSettings): Promise<void> {
+    return this.callMethod("push_pcr", "val", [arg0, arg1], ["val", "val"], []);
+  }
+  get_pcr(): Promise<PcrSetup[]> {
+    return this.callMethod("get_pcr", "val", [], [], []);
+  }
+  push_assembly(arg0: number[], arg1: any): Promise<void> {
+    return this.callMethod(
+      "push_assembly",
+      "val",
+      [new Uint32Array(arg0), arg1],
+      ["val", "val"],
+      []
+    );
+  }
+  get_assembly(): Promise<AssemblySetup[]> {
+    return this.callMethod("get_assembly", "val", [], [], []);
+  }
+  to_bytes(): Promise<Uint8Array> {
+    return this.callMethod("to_bytes", "binary", [], [], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+}
+
+export function newSeqHistory(arg0: Seq): Promise<SeqHistory> {
+  return workerRequest({
+    command: "new",
+    className: "SeqHistory",
+    args: [arg0],
+    specialArgs: ["obj"]
+  });
+}
+
+export function parse_history(arg0: ArrayBuffer): Promise<SeqHistory> {
+  return workerCall("parse_history", "obj", [arg0], ["binary"], [arg0]);
+}
+
+export interface HistoryEntry {
+  id: number;
+  parent?: number;
+  name: string;
+  params: any;
+  current: boolean;
+}
+
+export class SeqHistory extends RemoteObj {
+  push(arg0: string, arg1: any, arg2: Seq): Promise<void> {
+    return this.callMethod(
+      "push",
+      "val",
+      [arg0, arg1, arg2],
+      ["val", "val", "obj"],
+      []
+    );
+  }
+  current(): Promise<Seq> {
+    return this.callMethod("current", "obj", [], [], []);
+  }
+  can_undo(): Promise<boolean> {
+    return this.callMethod("can_undo", "val", [], [], []);
+  }
+  can_redo(): Promise<boolean> {
+    return this.callMethod("can_redo", "val", [], [], []);
+  }
+  undo(): Promise<Seq> {
+    return this.callMethod("undo", "obj", [], [], []);
+  }
+  redo(): Promise<Seq> {
+    return this.callMethod("redo", "obj", [], [], []);
+  }
+  checkout(arg0: number): Promise<Seq> {
+    return this.callMethod("checkout", "obj", [arg0], ["val"], []);
+  }
+  get_entries(): Promise<HistoryEntry[]> {
+    return this.callMethod("get_entries", "val", [], [], []);
+  }
+  to_bytes(): Promise<Uint8Array> {
+    return this.callMethod("to_bytes", "binary", [], [], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+}
+
 export type SeqSearchResult = {
   start: number;
   end: number;
   fwd: boolean;
 };
 
+export interface SeqChange {
+  kind: "substitution" | "insertion" | "deletion";
+  refStart: number;
+  refEnd: number;
+  sampleStart: number;
+  sampleEnd: number;
+  refBases: string;
+  sampleBases: string;
+  refFeatures: number[];
+  sampleFeatures: number[];
+}
+
+export interface SeqDiff {
+  sampleReversed: boolean;
+  sampleOffset: number;
+  identity: number;
+  changes: SeqChange[];
+}
+
+export interface OrfSettings {
+  minLength?: number;
+  alternativeStarts?: boolean;
+  table?: number;
+  bothStrands?: boolean;
+}
+
+export interface Orf {
+  start: number;
+  end: number;
+  fwd: boolean;
+  protein: string;
+}
+
+export interface FeatureTranslation {
+  protein: string;
+  table: number;
+  /** The feature's `/translation` qualifier */
+  expected: string | null;
+  firstMismatch: number | null;
+}
+
+export interface RestrictionSettings {
+  /** All enzymes if not given */
+  enzymes?: string[];
+}
+
+export interface RestrictionSite {
+  start: number;
+  end: number;
+  fwd: boolean;
+  cut: number;
+  cutBottom: number;
+}
+
+export interface EnzymeSites {
+  name: string;
+  site: string;
+  isoschizomers: string[];
+  sites: RestrictionSite[];
+}
+
+export interface DigestSummary {
+  unique: string[];
+  double: string[];
+  noncutters: string[];
+}
+
 export class Seq extends RemoteObj {
   set_name(arg0: string): Promise<Seq> {
     return this.callMethod("set_name", "obj", [arg0], ["val"], []);
@@ -212,6 +557,32 @@ export class Seq extends RemoteObj {
     );
   }
 
+  to_fasta(arg0: number, arg1?: string): Promise<Uint8Array> {
+    return this.callMethod("to_fasta", "binary", [arg0, arg1], ["val", "val"], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+
+  to_embl(): Promise<Uint8Array> {
+    return this.callMethod("to_embl", "binary", [], [], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+
+  to_json(): Promise<string> {
+    return this.callMethod("to_json", "val", [], [], []);
+  }
+
+  to_gff3(): Promise<Uint8Array> {
+    return this.callMethod("to_gff3", "binary", [], [], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+
+  merge_gff3(arg0: ArrayBuffer): Promise<Seq> {
+    return this.callMethod("merge_gff3", "obj", [arg0], ["binary"], [arg0]);
+  }
+
   get_feature_count(): Promise<number> {
     return this.callMethod("get_feature_count", "val", [], [], []);
   }
@@ -232,6 +603,49 @@ export class Seq extends RemoteObj {
       []
     );
   }
+  add_feature(
+    kind: string,
+    location: string,
+    qualifiers?: [string, string | null][]
+  ): Promise<Seq> {
+    return this.callMethod(
+      "add_feature",
+      "obj",
+      [kind, location, qualifiers],
+      ["val", "val", "val"],
+      []
+    );
+  }
+  set_feature_kind(idx: number, kind: string): Promise<Seq> {
+    return this.callMethod("set_feature_kind", "obj", [idx, kind], ["val", "val"], []);
+  }
+  set_feature_location(idx: number, location: string): Promise<Seq> {
+    return this.callMethod(
+      "set_feature_location",
+      "obj",
+      [idx, location],
+      ["val", "val"],
+      []
+    );
+  }
+  set_feature_qualifiers(
+    idx: number,
+    qualifiers: [string, string | null][]
+  ): Promise<Seq> {
+    return this.callMethod(
+      "set_feature_qualifiers",
+      "obj",
+      [idx, qualifiers],
+      ["val", "val"],
+      []
+    );
+  }
+  get_feature_location(idx: number): Promise<string | undefined> {
+    return this.callMethod("get_feature_location", "val", [idx], ["val"], []);
+  }
+  delete_features(idxes: Uint32Array): Promise<Seq> {
+    return this.callMethod("delete_features", "obj", [idxes], ["val"], []);
+  }
   get_feature_qualifier(arg0: string): Promise<string[]> {
     return this.callMethod(
       "get_feature_qualifier",
@@ -264,6 +678,79 @@ export class Seq extends RemoteObj {
       []
     );
   }
+  features_to_bed(bedgraph: boolean): Promise<Uint8Array> {
+    return this.callMethod("features_to_bed", "binary", [bedgraph], ["val"], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+  search_to_bed(
+    query: string,
+    max_res: number,
+    bedgraph: boolean
+  ): Promise<Uint8Array> {
+    return this.callMethod(
+      "search_to_bed",
+      "binary",
+      [query, max_res, bedgraph],
+      ["val", "val", "val"],
+      []
+    ).then(ab => new Uint8Array(ab));
+  }
+  diff(sample: Seq): Promise<SeqDiff> {
+    return this.callMethod("diff", "val", [sample], ["obj"], []);
+  }
+  transfer_features(
+    source: Seq,
+    settings?: { minIdentity?: number; minLength?: number }
+  ): Promise<Seq> {
+    return this.callMethod(
+      "transfer_features",
+      "obj",
+      [source, settings],
+      ["obj", "val"],
+      []
+    );
+  }
+  auto_annotate(settings?: {
+    mismatches?: number;
+    names?: string[];
+  }): Promise<Seq> {
+    return this.callMethod("auto_annotate", "obj", [settings], ["val"], []);
+  }
+  find_orfs(settings?: OrfSettings): Promise<Orf[]> {
+    return this.callMethod("find_orfs", "val", [settings], ["val"], []);
+  }
+  annotate_orfs(settings?: OrfSettings): Promise<Seq> {
+    return this.callMethod("annotate_orfs", "obj", [settings], ["val"], []);
+  }
+  restriction_sites(settings?: RestrictionSettings): Promise<EnzymeSites[]> {
+    return this.callMethod("restriction_sites", "val", [settings], ["val"], []);
+  }
+  restriction_summary(settings?: RestrictionSettings): Promise<DigestSummary> {
+    return this.callMethod("restriction_summary", "val", [settings], ["val"], []);
+  }
+  /** Stops are translated as `*` */
+  translate_range(
+    start: number,
+    end: number,
+    fwd: boolean,
+    table: number
+  ): Promise<string> {
+    return this.callMethod(
+      "translate_range",
+      "val",
+      [start, end, fwd, table],
+      ["val", "val", "val", "val"],
+      []
+    );
+  }
+  translate_feature(idx: number): Promise<FeatureTranslation> {
+    return this.callMethod("translate_feature", "val", [idx], ["val"], []);
+  }
+  /** The features whose `/translation` doesn't match their sequence */
+  check_translations(): Promise<Uint32Array> {
+    return this.callMethod("check_translations", "val", [], [], []);
+  }
   get_seq_slice(arg0: number, arg1: number): Promise<string> {
     return this.callMethod(
       "get_seq_slice",
@@ -288,11 +775,90 @@ export class Seq extends RemoteObj {
     return this.callMethod("set_origin", "obj", [arg0], ["val"], []);
   }
 
+  insert(pos: number, bases: string): Promise<Seq> {
+    return this.callMethod("insert", "obj", [pos, bases], ["val", "val"], []);
+  }
+
+  delete(start: number, end: number): Promise<Seq> {
+    return this.callMethod("delete", "obj", [start, end], ["val", "val"], []);
+  }
+
+  replace(start: number, end: number, bases: string): Promise<Seq> {
+    return this.callMethod(
+      "replace",
+      "obj",
+      [start, end, bases],
+      ["val", "val", "val"],
+      []
+    );
+  }
+
   clone(): Promise<Seq> {
     return this.callMethod("clone", "obj", [], [], []);
   }
 }
 
+export interface ColumnMapping {
+  delimiter?: string;
+  name?: string;
+  seq?: string;
+  desc?: string;
+}
+
+export interface PrimerTable {
+  primers: { name: string; seq: string; desc: string }[];
+  errors: { line: number; message: string }[];
+}
+
+export function parse_primer_table(
+  arg0: ArrayBuffer,
+  arg1?: ColumnMapping
+): Promise<PrimerTable> {
+  return workerCall(
+    "parse_primer_table",
+    "val",
+    [arg0, arg1],
+    ["binary", "val"],
+    [arg0]
+  );
+}
+
+export function write_primer_table(
+  arg0: any[],
+  arg1: boolean
+): Promise<Uint8Array> {
+  return workerCall(
+    "write_primer_table",
+    "binary",
+    [arg0, arg1],
+    ["val", "val"],
+    []
+  ).then(ab => new Uint8Array(ab));
+}
+
+export interface LibraryEntry {
+  name: string;
+  kind: string;
+  len: number;
+}
+
+export function get_feature_library(): Promise<LibraryEntry[]> {
+  return workerCall("get_feature_library", "val", [], [], []);
+}
+
+export interface Enzyme {
+  name: string;
+  site: string;
+  /** Top strand cut, from the start of `site` */
+  cut: number;
+  cutBottom: number;
+  isoschizomers: string[];
+}
+
+export function get_enzymes(): Promise<Enzyme[]> {
+  return workerCall("get_enzymes", "val", [], [], []);
+}
+
 export function newPcrer(arg0: Seq, arg1: any[], arg2: any): Promise<Pcrer> {
   return workerRequest({
     command: "new",
@@ -346,6 +912,18 @@ export class PcrResults extends RemoteObj {
     return this.callMethod("annotate_matches", "obj", [idxes], ["val"], []);
   }
 
+  products_to_bed(bedgraph: boolean): Promise<Uint8Array> {
+    return this.callMethod("products_to_bed", "binary", [bedgraph], ["val"], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+
+  matches_to_bed(bedgraph: boolean): Promise<Uint8Array> {
+    return this.callMethod("matches_to_bed", "binary", [bedgraph], ["val"], []).then(
+      ab => new Uint8Array(ab)
+    );
+  }
+
   run(arg0: any): Promise<Pcrer> {
     return this.callMethod("run", "obj", [arg0], ["val"], []);
   }
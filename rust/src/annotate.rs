@@ -0,0 +1,171 @@
+//! Adding features for the common elements in `feature_library`, found with
+//! `search::search_mismatches` on both strands.
+use crate::feature_library::{LibraryFeature, LIBRARY};
+use crate::location::strand_range;
+use crate::search::search_mismatches;
+use gb_io::seq::*;
+use wasm_bindgen::prelude::*;
+
+/// Maximum number of matches of each library element
+const MAX_RES: usize = 100;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotateSettings {
+    #[serde(default = "default_mismatches")]
+    pub mismatches: usize,
+    /// names of the library elements to look for, all of them if not given
+    #[serde(default)]
+    pub names: Option<Vec<String>>,
+}
+
+fn default_mismatches() -> usize {
+    2
+}
+
+impl Default for AnnotateSettings {
+    fn default() -> AnnotateSettings {
+        AnnotateSettings {
+            mismatches: default_mismatches(),
+            names: None,
+        }
+    }
+}
+
+fn selected(settings: &AnnotateSettings) -> Result<Vec<&'static LibraryFeature>, String> {
+    match settings.names {
+        None => Ok(LIBRARY.iter().collect()),
+        Some(ref names) => names
+            .iter()
+            .map(|name| {
+                LIBRARY
+                    .iter()
+                    .find(|f| f.name == name.as_str())
+                    .ok_or_else(|| format!("Unknown library feature: {}", name))
+            })
+            .collect(),
+    }
+}
+
+pub fn auto_annotate(seq: &Seq, settings: &AnnotateSettings) -> Result<Seq, String> {
+    let mut res = seq.clone();
+    for lib in selected(settings)? {
+        let kind = FeatureKind::from(lib.kind);
+        for (hit, mismatches) in search_mismatches(seq, lib.seq, settings.mismatches, MAX_RES)
+            .into_iter()
+            .take(MAX_RES)
+        {
+            let location = strand_range(hit.start, hit.end, hit.fwd, seq.len());
+            if res
+                .features
+                .iter()
+                .any(|f| f.kind == kind && f.location == location)
+            {
+                continue;
+            }
+            let mut qualifiers = vec![(qualifier_key!("label"), Some(lib.name.to_string()))];
+            qualifiers.extend(
+                lib.qualifiers
+                    .iter()
+                    .map(|&(k, v)| (QualifierKey::from(k), Some(v.to_string()))),
+            );
+            if mismatches > 0 {
+                qualifiers.push((
+                    qualifier_key!("note"),
+                    Some(format!(
+                        "mismatches to the library sequence: {}",
+                        mismatches
+                    )),
+                ));
+            }
+            res.features.push(Feature {
+                kind: kind.clone(),
+                location,
+                qualifiers,
+            });
+        }
+    }
+    Ok(res)
+}
+
+#[derive(Serialize)]
+struct LibraryEntry {
+    name: &'static str,
+    kind: &'static str,
+    len: usize,
+}
+
+/// The elements `auto_annotate` can look for
+#[wasm_bindgen]
+pub fn get_feature_library() -> JsValue {
+    let entries: Vec<_> = LIBRARY
+        .iter()
+        .map(|f| LibraryEntry {
+            name: f.name,
+            kind: f.kind,
+            len: f.seq.len(),
+        })
+        .collect();
+    JsValue::from_serde(&entries).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+    use bio::alphabets::dna;
+
+    fn library_seq(name: &str) -> &'static [u8] {
+        LIBRARY.iter().find(|f| f.name == name).unwrap().seq
+    }
+
+    #[test]
+    fn test_auto_annotate() {
+        // T7 promoter with a mismatch on the reverse strand, across the
+        // origin, and a lac operator
+        let mut t7 = library_seq("T7 promoter").to_vec();
+        t7[4] = b'T';
+        let t7 = dna::revcomp(&t7);
+        let mut bases = t7[10..].to_vec();
+        bases.extend_from_slice(b"cccccccccc");
+        bases.extend_from_slice(library_seq("lac operator"));
+        bases.extend_from_slice(b"cccccccccc");
+        bases.extend_from_slice(&t7[..10]);
+        let seq = Seq {
+            seq: bases,
+            topology: Topology::Circular,
+            ..Seq::empty()
+        };
+        let settings = AnnotateSettings {
+            mismatches: 1,
+            names: Some(vec!["T7 promoter".into(), "lac operator".into()]),
+        };
+        let res = auto_annotate(&seq, &settings).unwrap();
+        let locations: Vec<_> = res
+            .features
+            .iter()
+            .map(|f| location::format(&f.location))
+            .collect();
+        assert_eq!(locations, vec!["complement(join(47..56,1..9))", "20..36"]);
+        assert_eq!(
+            res.features[0]
+                .qualifier_values(qualifier_key!("note"))
+                .next(),
+            Some("mismatches to the library sequence: 1")
+        );
+        assert_eq!(res.features[1].kind, FeatureKind::from("protein_bind"));
+        // nothing new the second time
+        let again = auto_annotate(&res, &settings).unwrap();
+        assert_eq!(again.features.len(), 2);
+        let settings = AnnotateSettings {
+            mismatches: 0,
+            names: Some(vec!["T7 promoter".into()]),
+        };
+        assert!(auto_annotate(&seq, &settings).unwrap().features.is_empty());
+        let settings = AnnotateSettings {
+            mismatches: 0,
+            names: Some(vec!["pUC ori".into()]),
+        };
+        assert!(auto_annotate(&seq, &settings).is_err());
+    }
+}
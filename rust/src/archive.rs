@@ -0,0 +1,106 @@
+use flate2::read::MultiGzDecoder;
+use std::borrow::Cow;
+use std::io::{Cursor, Read};
+
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
+
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut res = Vec::new();
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut res)
+        .map_err(|e| format!("Decompressing failed: {}", e))?;
+    Ok(res)
+}
+
+/// Decompress `data` if it is gzipped, otherwise return it unchanged
+pub fn maybe_gunzip(data: &[u8]) -> Result<Cow<[u8]>, String> {
+    if is_gzip(data) {
+        Ok(Cow::Owned(gunzip(data)?))
+    } else {
+        Ok(Cow::Borrowed(data))
+    }
+}
+
+/// Extract all files in a zip archive. Directories and metadata added by
+/// macOS are skipped, errors are reported for each entry separately.
+pub fn unzip(data: &[u8]) -> Result<Vec<(String, Result<Vec<u8>, String>)>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Reading zip archive failed: {}", e))?;
+    let mut res = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = match archive.by_index(i) {
+            Ok(file) => file,
+            Err(e) => {
+                res.push((format!("#{}", i), Err(format!("{}", e))));
+                continue;
+            }
+        };
+        let name = file.name().to_string();
+        let basename = name.rsplit('/').next().unwrap_or_default();
+        if file.is_dir() || name.starts_with("__MACOSX/") || basename.starts_with('.') {
+            continue;
+        }
+        let mut contents = Vec::new();
+        let contents = match file.read_to_end(&mut contents) {
+            Ok(_) => Ok(contents),
+            Err(e) => Err(format!("Decompressing failed: {}", e)),
+        };
+        res.push((name, contents));
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use zip::{write::FileOptions, ZipWriter};
+
+    pub(crate) const FASTA: &[u8] = b">a\nACGT\n";
+
+    pub(crate) fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut e = GzEncoder::new(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    pub(crate) fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut w = ZipWriter::new(Cursor::new(Vec::new()));
+        for &(name, data) in entries {
+            w.start_file(name, FileOptions::default()).unwrap();
+            w.write_all(data).unwrap();
+        }
+        w.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_gunzip() {
+        let gz = gzip(FASTA);
+        assert!(is_gzip(&gz));
+        assert_eq!(gunzip(&gz).unwrap(), FASTA.to_vec());
+        assert_eq!(&*maybe_gunzip(&gz).unwrap(), FASTA);
+        match maybe_gunzip(FASTA).unwrap() {
+            Cow::Borrowed(d) => assert_eq!(d, FASTA),
+            Cow::Owned(_) => panic!("not gzipped"),
+        }
+        assert!(gunzip(&gz[..gz.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_unzip() {
+        let data = zip(&[
+            ("seqs/a.fasta", FASTA),
+            ("__MACOSX/seqs/._a.fasta", &b"junk"[..]),
+            ("seqs/.DS_Store", &b"junk"[..]),
+            ("b.txt", &b"not a sequence"[..]),
+        ]);
+        let entries = unzip(&data).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.0.as_str()).collect();
+        assert_eq!(names, vec!["seqs/a.fasta", "b.txt"]);
+        assert_eq!(entries[0].1, Ok(FASTA.to_vec()));
+        assert!(unzip(FASTA).is_err());
+    }
+}
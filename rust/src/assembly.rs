@@ -12,10 +12,11 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 use crate::assembly_diagram;
+use crate::sbol;
 
-#[derive(Deserialize)]
-struct JsAssemblySettings {
-    limit: usize,
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct JsAssemblySettings {
+    pub(crate) limit: usize,
 }
 
 #[wasm_bindgen]
@@ -124,6 +125,16 @@ impl AssemblyResult {
         seq.name = Some(sanitise_name(&product.desc));
         JsSeq(Rc::new(seq))
     }
+    pub fn to_sbol_linear(&self, index: usize, namespace: &str) -> String {
+        self.to_sbol_impl(&self.linear, index, namespace)
+    }
+    pub fn to_sbol_circular(&self, index: usize, namespace: &str) -> String {
+        self.to_sbol_impl(&self.circular, index, namespace)
+    }
+    fn to_sbol_impl(&self, array: &[Product], index: usize, namespace: &str) -> String {
+        let product = &array[index];
+        sbol::export_product(namespace, &product.desc, &self.seqs(), &product.path)
+    }
     fn seqs(&self) -> Vec<&Seq> {
         self.seqs.iter().map(|s| s.as_ref()).collect()
     }
@@ -0,0 +1,226 @@
+//! BED and bedGraph export, for viewing features, search hits and PCR
+//! results in genome browsers. Intervals crossing the origin of a circular
+//! sequence are split into two records.
+use crate::location::for_each_range;
+use crate::search::SeqSearchResult;
+use gb_io::seq::*;
+use std::io::{self, Write};
+
+/// An interval in sequence coordinates, `end` may be past the end (or
+/// `start` before the start) of a circular sequence
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+    pub name: String,
+    /// `None` if the interval has no strand
+    pub fwd: Option<bool>,
+}
+
+/// BED is tab separated, and browsers split the chromosome name on whitespace
+fn chrom(seq: &Seq) -> String {
+    let name = seq.name.clone().unwrap_or_else(|| "Untitled".into());
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Convert to zero or more `(start, end)` pairs within `0..len`
+fn normalise(seq: &Seq, start: i64, end: i64) -> Vec<(i64, i64)> {
+    let len = seq.len();
+    if len == 0 || end <= start {
+        return Vec::new();
+    }
+    if !seq.is_circular() {
+        let (start, end) = (start.max(0), end.min(len));
+        return if start < end {
+            vec![(start, end)]
+        } else {
+            Vec::new()
+        };
+    }
+    let span = (end - start).min(len);
+    let start = start.rem_euclid(len);
+    let end = start + span;
+    if end > len {
+        vec![(start, len), (0, end - len)]
+    } else {
+        vec![(start, end)]
+    }
+}
+
+pub fn write_bed<W: Write>(mut w: W, seq: &Seq, intervals: &[Interval]) -> io::Result<()> {
+    let chrom = chrom(seq);
+    for i in intervals {
+        let name = i
+            .name
+            .replace(|c: char| c == '\t' || c == '\n' || c == '\r', " ");
+        let strand = match i.fwd {
+            Some(true) => "+",
+            Some(false) => "-",
+            None => ".",
+        };
+        for (start, end) in normalise(seq, i.start, i.end) {
+            writeln!(w, "{}\t{}\t{}\t{}\t0\t{}", chrom, start, end, name, strand)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the number of intervals covering each position, omitting positions
+/// which aren't covered
+pub fn write_bedgraph<W: Write>(
+    mut w: W,
+    seq: &Seq,
+    track_name: &str,
+    intervals: &[Interval],
+) -> io::Result<()> {
+    let chrom = chrom(seq);
+    let mut events = Vec::new();
+    for i in intervals {
+        for (start, end) in normalise(seq, i.start, i.end) {
+            events.push((start, 1));
+            events.push((end, -1));
+        }
+    }
+    events.sort();
+    writeln!(
+        w,
+        "track type=bedGraph name=\"{}\"",
+        track_name.replace('"', "'")
+    )?;
+    let mut depth = 0;
+    let mut last = 0;
+    for (pos, change) in events {
+        if pos > last && depth > 0 {
+            writeln!(w, "{}\t{}\t{}\t{}", chrom, last, pos, depth)?;
+        }
+        depth += change;
+        last = pos;
+    }
+    Ok(())
+}
+
+pub fn write<W: Write>(
+    w: W,
+    seq: &Seq,
+    track_name: &str,
+    intervals: &[Interval],
+    bedgraph: bool,
+) -> io::Result<()> {
+    if bedgraph {
+        write_bedgraph(w, seq, track_name, intervals)
+    } else {
+        write_bed(w, seq, intervals)
+    }
+}
+
+const NAME_KEYS: &[QualifierKey] = &[
+    qualifier_key!("label"),
+    qualifier_key!("gene"),
+    qualifier_key!("locus_tag"),
+    qualifier_key!("product"),
+    qualifier_key!("note"),
+];
+
+/// One interval per range of each feature, named after the feature
+pub fn features(seq: &Seq) -> Vec<Interval> {
+    let mut res = Vec::new();
+    for f in &seq.features {
+        let kind: &str = &f.kind;
+        let name = NAME_KEYS
+            .iter()
+            .filter_map(|k| f.qualifier_values(k.clone()).next())
+            .next()
+            .unwrap_or(kind);
+        let mut add = |start, end, fwd| {
+            res.push(Interval {
+                start,
+                end,
+                name: name.to_string(),
+                fwd: Some(fwd),
+            })
+        };
+        for_each_range(&f.location, &mut add, true);
+    }
+    res
+}
+
+pub fn search_results(results: &[SeqSearchResult], name: &str) -> Vec<Interval> {
+    results
+        .iter()
+        .map(|r| Interval {
+            start: r.start,
+            end: r.end,
+            name: name.to_string(),
+            fwd: Some(r.fwd),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    fn seq() -> Seq {
+        Seq {
+            name: Some("test seq".into()),
+            topology: Topology::Circular,
+            seq: b"acgtacgtacgtacgtacgt".to_vec(),
+            features: vec![Feature {
+                kind: feature_kind!("CDS"),
+                location: location::parse("complement(join(16..20,1..4))").unwrap(),
+                qualifiers: vec![(qualifier_key!("gene"), Some("abc".into()))],
+            }],
+            ..Seq::empty()
+        }
+    }
+
+    #[test]
+    fn test_bed() {
+        let seq = seq();
+        let mut intervals = features(&seq);
+        intervals.push(Interval {
+            start: -2,
+            end: 3,
+            name: "hit".into(),
+            fwd: None,
+        });
+        let mut res = Vec::new();
+        write_bed(&mut res, &seq, &intervals).unwrap();
+        assert_eq!(
+            String::from_utf8(res).unwrap(),
+            "test_seq\t15\t20\tabc\t0\t-\n\
+             test_seq\t0\t4\tabc\t0\t-\n\
+             test_seq\t18\t20\thit\t0\t.\n\
+             test_seq\t0\t3\thit\t0\t.\n"
+        );
+    }
+
+    #[test]
+    fn test_bedgraph() {
+        let seq = seq();
+        let intervals = vec![
+            Interval {
+                start: 18,
+                end: 23,
+                name: "a".into(),
+                fwd: Some(true),
+            },
+            Interval {
+                start: 2,
+                end: 5,
+                name: "b".into(),
+                fwd: Some(true),
+            },
+        ];
+        let mut res = Vec::new();
+        write_bedgraph(&mut res, &seq, "hits", &intervals).unwrap();
+        assert_eq!(
+            String::from_utf8(res).unwrap(),
+            "track type=bedGraph name=\"hits\"\n\
+             test_seq\t0\t2\t1\n\
+             test_seq\t2\t3\t2\n\
+             test_seq\t3\t5\t1\n\
+             test_seq\t18\t20\t1\n"
+        );
+    }
+}
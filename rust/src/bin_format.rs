@@ -0,0 +1,457 @@
+//! The binary sequence format used to store fragments in IndexedDB: a
+//! bincode encoded version tag followed by a bincode encoded `Seq` (version
+//! 2) or `PackedSeq` (version 3).
+//!
+//! Version 1 predates this code and its layout wasn't recorded, so reading
+//! it is out of scope: `decode` reports it as an unknown version.
+//!
+//! Whenever `gb-io::Seq` breaks compatibility, bump `BIN_SEQ_FORMAT_VERSION`,
+//! freeze a copy of the old layout in a new module below, add a migration in
+//! `decode` and a blob to `test_data` so old fragments can still be read.
+use bincode::{deserialize, deserialize_from, serialize, serialize_into};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use gb_io::seq::*;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct BinSeqVersion(pub u32);
+
+// update this if/when `gb-io::Seq` breaks compatibility
+pub const BIN_SEQ_FORMAT_VERSION: BinSeqVersion = BinSeqVersion(3);
+
+/// The `gb-io::Seq` layout of version 2, frozen so version 2 blobs can still
+/// be read when gb-io changes. `Date`, `GapLength`, `Reference`, `Source` and
+/// `Topology` haven't changed since, freeze them here too if they do.
+mod v2 {
+    use gb_io::seq::{
+        After, Before, Date, Feature, FeatureKind, GapLength, Location, QualifierKey, Reference,
+        Seq, Source, Topology,
+    };
+
+    #[derive(Deserialize)]
+    pub enum LocationV2 {
+        /// `(start, before)`, `(end, after)`
+        Range((i64, bool), (i64, bool)),
+        Between(i64, i64),
+        Complement(Box<LocationV2>),
+        Join(Vec<LocationV2>),
+        Order(Vec<LocationV2>),
+        Bond(Vec<LocationV2>),
+        OneOf(Vec<LocationV2>),
+        External(String, Option<Box<LocationV2>>),
+        Gap(GapLength),
+    }
+
+    #[derive(Deserialize)]
+    pub struct FeatureV2 {
+        pub kind: String,
+        pub location: LocationV2,
+        pub qualifiers: Vec<(String, Option<String>)>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct SeqV2 {
+        pub name: Option<String>,
+        pub topology: Topology,
+        pub date: Option<Date>,
+        pub len: Option<usize>,
+        pub molecule_type: Option<String>,
+        pub division: String,
+        pub definition: Option<String>,
+        pub accession: Option<String>,
+        pub version: Option<String>,
+        pub source: Option<Source>,
+        pub dblink: Option<String>,
+        pub keywords: Option<String>,
+        pub references: Vec<Reference>,
+        pub comments: Vec<String>,
+        pub seq: Vec<u8>,
+        pub contig: Option<LocationV2>,
+        pub features: Vec<FeatureV2>,
+    }
+
+    impl From<LocationV2> for Location {
+        fn from(l: LocationV2) -> Location {
+            use LocationV2 as L;
+            let list =
+                |ls: Vec<L>| -> Vec<Location> { ls.into_iter().map(Location::from).collect() };
+            match l {
+                L::Range((a, before), (b, after)) => {
+                    Location::Range((a, Before(before)), (b, After(after)))
+                }
+                L::Between(a, b) => Location::Between(a, b),
+                L::Complement(l) => Location::Complement(Box::new((*l).into())),
+                L::Join(ls) => Location::Join(list(ls)),
+                L::Order(ls) => Location::Order(list(ls)),
+                L::Bond(ls) => Location::Bond(list(ls)),
+                L::OneOf(ls) => Location::OneOf(list(ls)),
+                L::External(accession, l) => {
+                    Location::External(accession, l.map(|l| Box::new((*l).into())))
+                }
+                L::Gap(len) => Location::Gap(len),
+            }
+        }
+    }
+
+    impl From<SeqV2> for Seq {
+        fn from(s: SeqV2) -> Seq {
+            Seq {
+                name: s.name,
+                topology: s.topology,
+                date: s.date,
+                len: s.len,
+                molecule_type: s.molecule_type,
+                division: s.division,
+                definition: s.definition,
+                accession: s.accession,
+                version: s.version,
+                source: s.source,
+                dblink: s.dblink,
+                keywords: s.keywords,
+                references: s.references,
+                comments: s.comments,
+                seq: s.seq,
+                contig: s.contig.map(Location::from),
+                features: s
+                    .features
+                    .into_iter()
+                    .map(|f| Feature {
+                        kind: FeatureKind::from(f.kind.as_str()),
+                        location: f.location.into(),
+                        qualifiers: f
+                            .qualifiers
+                            .into_iter()
+                            .map(|(k, v)| (QualifierKey::from(k.as_str()), v))
+                            .collect(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+}
+
+/// Version 3 stores the bases separately from everything else, packed into
+/// 2 bits each, which makes large genomes roughly 4 times smaller
+#[derive(Serialize, Deserialize)]
+struct PackedSeq {
+    /// the `Seq` with an empty `seq`, bincode encoded and deflated
+    meta: Vec<u8>,
+    len: u64,
+    /// `ACGT` packed into 2 bits each, 4 per byte, starting with the low bits
+    bases: Vec<u8>,
+    /// runs of anything that isn't `ACGTacgt` as `(start, raw bytes)`
+    exceptions: Vec<(u64, Vec<u8>)>,
+    /// runs of lowercase bases as `(start, end)`
+    lowercase: Vec<(u64, u64)>,
+}
+
+/// A `Seq` without its bases, borrowing everything else. The fields are
+/// those of `gb-io::Seq` in the same order, so this serializes exactly like
+/// a `Seq` with an empty `seq`.
+#[derive(Serialize)]
+struct SeqMeta<'a> {
+    name: &'a Option<String>,
+    topology: &'a Topology,
+    date: &'a Option<Date>,
+    len: &'a Option<usize>,
+    molecule_type: &'a Option<String>,
+    division: &'a String,
+    definition: &'a Option<String>,
+    accession: &'a Option<String>,
+    version: &'a Option<String>,
+    source: &'a Option<Source>,
+    dblink: &'a Option<String>,
+    keywords: &'a Option<String>,
+    references: &'a Vec<Reference>,
+    comments: &'a Vec<String>,
+    seq: &'a [u8],
+    contig: &'a Option<Location>,
+    features: &'a Vec<Feature>,
+}
+
+impl<'a> SeqMeta<'a> {
+    fn new(seq: &'a Seq) -> SeqMeta<'a> {
+        SeqMeta {
+            name: &seq.name,
+            topology: &seq.topology,
+            date: &seq.date,
+            len: &seq.len,
+            molecule_type: &seq.molecule_type,
+            division: &seq.division,
+            definition: &seq.definition,
+            accession: &seq.accession,
+            version: &seq.version,
+            source: &seq.source,
+            dblink: &seq.dblink,
+            keywords: &seq.keywords,
+            references: &seq.references,
+            comments: &seq.comments,
+            seq: &[],
+            contig: &seq.contig,
+            features: &seq.features,
+        }
+    }
+}
+
+fn base_code(b: u8) -> Option<u8> {
+    match b {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Find the runs of bytes in `seq` matching `pred` as `(start, end)`
+fn runs(seq: &[u8], pred: impl Fn(u8) -> bool) -> Vec<(usize, usize)> {
+    let mut res = Vec::new();
+    let mut start = None;
+    for (i, &b) in seq.iter().enumerate() {
+        match (pred(b), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                res.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        res.push((s, seq.len()));
+    }
+    res
+}
+
+fn pack(meta: &SeqMeta, seq: &[u8]) -> Result<PackedSeq, String> {
+    let mut bases = vec![0u8; (seq.len() + 3) / 4];
+    for (i, &b) in seq.iter().enumerate() {
+        bases[i / 4] |= base_code(b).unwrap_or(0) << ((i % 4) * 2);
+    }
+    let exceptions = runs(&seq, |b| base_code(b).is_none())
+        .into_iter()
+        .map(|(start, end)| (start as u64, seq[start..end].to_vec()))
+        .collect();
+    let lowercase = runs(&seq, |b| b.is_ascii_lowercase())
+        .into_iter()
+        .map(|(start, end)| (start as u64, end as u64))
+        .collect();
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    serialize_into(&mut encoder, meta).map_err(|e| format!("Couldn't encode: {}", e))?;
+    let meta = encoder
+        .finish()
+        .map_err(|e| format!("Compression failed: {}", e))?;
+    Ok(PackedSeq {
+        meta,
+        len: seq.len() as u64,
+        bases,
+        exceptions,
+        lowercase,
+    })
+}
+
+fn unpack(p: PackedSeq) -> Result<Seq, String> {
+    let mut meta = Vec::new();
+    DeflateDecoder::new(&p.meta[..])
+        .read_to_end(&mut meta)
+        .map_err(|e| format!("Decompression failed: {}", e))?;
+    let mut seq: Seq = deserialize(&meta).map_err(|e| format!("Decoding failed: {}", e))?;
+    // `len` comes from the data, which may be corrupt, and usize may be 32 bits
+    let len = usize::try_from(p.len)
+        .ok()
+        .filter(|&len| len / 4 + (len % 4 != 0) as usize == p.bases.len())
+        .ok_or_else(|| String::from("Decoding failed: wrong number of bases"))?;
+    seq.seq = (0..len)
+        .map(|i| b"ACGT"[((p.bases[i / 4] >> ((i % 4) * 2)) & 0b11) as usize])
+        .collect();
+    let invalid_run = || String::from("Decoding failed: invalid case run");
+    for (start, end) in p.lowercase {
+        let start = usize::try_from(start).map_err(|_| invalid_run())?;
+        let end = usize::try_from(end).map_err(|_| invalid_run())?;
+        let range = seq.seq.get_mut(start..end).ok_or_else(invalid_run)?;
+        range.make_ascii_lowercase();
+    }
+    let invalid_exception = || String::from("Decoding failed: invalid exception");
+    for (start, raw) in p.exceptions {
+        let start = usize::try_from(start).map_err(|_| invalid_exception())?;
+        let end = start.checked_add(raw.len()).ok_or_else(invalid_exception)?;
+        let range = seq.seq.get_mut(start..end).ok_or_else(invalid_exception)?;
+        range.copy_from_slice(&raw);
+    }
+    Ok(seq)
+}
+
+/// Decode a sequence stored in any version of the binary format
+pub fn decode(mut data: &[u8]) -> Result<Seq, String> {
+    // read version tag
+    let ver: BinSeqVersion =
+        deserialize_from(&mut data).map_err(|e| format!("Decoding header failed: {}", e))?;
+    let decode_err = |e: bincode::Error| format!("Decoding failed: {}", e);
+    match ver.0 {
+        2 => deserialize_from(&mut data)
+            .map(|s: v2::SeqV2| s.into())
+            .map_err(decode_err),
+        // the current version
+        3 => unpack(deserialize_from(&mut data).map_err(decode_err)?),
+        v => Err(format!(
+            "Can't decode version {} of binary sequence format",
+            v
+        )),
+    }
+}
+
+fn encode_parts(meta: &SeqMeta, bases: &[u8]) -> Result<Vec<u8>, String> {
+    let mut res = Vec::new();
+    let mut buf = &mut res;
+    // write version tag
+    serialize_into(&mut buf, &BIN_SEQ_FORMAT_VERSION).expect("Writing header failed");
+    let packed = pack(meta, bases)?;
+    serialize_into(&mut buf, &packed).map_err(|e| format!("Couldn't encode: {}", e))?;
+    Ok(res)
+}
+
+/// Encode a sequence using the current version of the binary format
+pub fn encode(seq: &Seq) -> Result<Vec<u8>, String> {
+    encode_parts(&SeqMeta::new(seq), &seq.seq)
+}
+
+/// Encode everything but the bases of `seq`, which `decode` returns with an
+/// empty `seq`. Together with `encode_bases` this stores versions of a
+/// sequence sharing their bases only once.
+pub fn encode_without_bases(seq: &Seq) -> Result<Vec<u8>, String> {
+    encode_parts(&SeqMeta::new(seq), &[])
+}
+
+/// Encode an otherwise empty sequence with these bases
+pub fn encode_bases(bases: &[u8]) -> Result<Vec<u8>, String> {
+    encode_parts(&SeqMeta::new(&Seq::empty()), bases)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    // frozen blobs, these must never be regenerated
+    const V2: &[u8] = include_bytes!("../test_data/seq_v2.bin");
+    const V3: &[u8] = include_bytes!("../test_data/seq_v3.bin");
+
+    fn check(seq: &Seq) {
+        assert_eq!(seq.name, Some("test".into()));
+        assert!(seq.is_circular());
+        assert_eq!(seq.definition, Some("A frozen test sequence".into()));
+        assert_eq!(
+            seq.seq,
+            b"atgaaagcaattttcgtactgaaaggttggtggcgcacttcctga".to_vec()
+        );
+        assert_eq!(seq.features.len(), 2);
+        assert_eq!(seq.features[0].kind, feature_kind!("CDS"));
+        assert_eq!(
+            location::format(&seq.features[0].location),
+            "complement(join(1..9,31..45))"
+        );
+        assert_eq!(
+            seq.features[0].qualifiers,
+            vec![
+                (qualifier_key!("gene"), Some("testA".into())),
+                (QualifierKey::from("pseudo"), None)
+            ]
+        );
+        assert_eq!(location::format(&seq.features[1].location), "<10..>30");
+    }
+
+    #[test]
+    fn test_v2() {
+        check(&decode(V2).unwrap());
+    }
+
+    #[test]
+    fn test_v3() {
+        check(&decode(V3).unwrap());
+    }
+
+    #[test]
+    fn test_packing() {
+        let seq = Seq {
+            seq: b"ACGTacgtNNNNacgRYacgtTTTTGGGGCCCCA-A".to_vec(),
+            ..decode(V2).unwrap()
+        };
+        let encoded = encode(&seq).unwrap();
+        assert_eq!(decode(&encoded).unwrap(), seq);
+        let empty = Seq {
+            seq: Vec::new(),
+            ..seq
+        };
+        assert_eq!(decode(&encode(&empty).unwrap()).unwrap(), empty);
+        // compare with version 2
+        let big = Seq {
+            seq: b"acgt".iter().cycle().take(100_000).cloned().collect(),
+            ..Seq::empty()
+        };
+        let mut v2 = serialize(&BinSeqVersion(2)).unwrap();
+        v2.extend(serialize(&big).unwrap());
+        let v3 = encode(&big).unwrap();
+        assert!(v3.len() * 3 < v2.len());
+        assert_eq!(decode(&v2).unwrap(), decode(&v3).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let seq = decode(V2).unwrap();
+        assert_eq!(decode(&encode(&seq).unwrap()).unwrap(), seq);
+        // the meta data is encoded without copying the sequence
+        let meta = serialize(&SeqMeta::new(&seq)).unwrap();
+        let empty = Seq {
+            seq: Vec::new(),
+            ..seq
+        };
+        assert_eq!(meta, serialize(&empty).unwrap());
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let packed = |len: u64, bases: Vec<u8>, exceptions, lowercase| {
+            let mut data = serialize(&BIN_SEQ_FORMAT_VERSION).unwrap();
+            let meta = pack(&SeqMeta::new(&Seq::empty()), &[]).unwrap().meta;
+            data.extend(
+                serialize(&PackedSeq {
+                    meta,
+                    len,
+                    bases,
+                    exceptions,
+                    lowercase,
+                })
+                .unwrap(),
+            );
+            data
+        };
+        assert!(decode(&packed(4, vec![0], Vec::new(), Vec::new())).is_ok());
+        // a length which wraps around when rounded up to whole bytes
+        assert!(decode(&packed(u64::max_value(), vec![0], Vec::new(), Vec::new())).is_err());
+        assert!(decode(&packed(5, vec![0], Vec::new(), Vec::new())).is_err());
+        let exception = vec![(u64::max_value(), vec![b'N'])];
+        assert!(decode(&packed(4, vec![0], exception, Vec::new())).is_err());
+        let run = vec![(2, u64::max_value())];
+        assert!(decode(&packed(4, vec![0], Vec::new(), run)).is_err());
+    }
+
+    #[test]
+    fn test_v2_layout() {
+        // the frozen layout still matches `Seq`, so version 2 blobs written
+        // by the current gb-io decode to the same sequence
+        let seq = decode(V3).unwrap();
+        let migrated: Seq = deserialize::<v2::SeqV2>(&serialize(&seq).unwrap())
+            .unwrap()
+            .into();
+        assert_eq!(migrated, seq);
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        assert!(decode(&[99, 0, 0, 0]).is_err());
+        assert!(decode(&[1, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Joining sequences end to end, without the homology `Assembly` needs.
+//! Fragments may be reverse complemented first, and features spanning the
+//! origin of a circular fragment are split since the fragment is opened there.
+use gb_io::seq::*;
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcatSettings {
+    /// one flag per fragment, missing flags mean forward
+    #[serde(default)]
+    pub reverse: Vec<bool>,
+    #[serde(default)]
+    pub circular: bool,
+    /// add a feature at each junction between fragments
+    #[serde(default)]
+    pub junctions: bool,
+}
+
+/// Shift `l` by `offset` into a product of length `total`. `len` is the
+/// length of the fragment, `circular` whether ranges may extend past its end.
+fn shift_location(
+    l: &Location,
+    offset: i64,
+    len: i64,
+    circular: bool,
+    total: i64,
+    circular_product: bool,
+) -> Option<Location> {
+    use Location::*;
+    let shift = |l: &Location| shift_location(l, offset, len, circular, total, circular_product);
+    let list = |ls: &[Location]| -> Option<Vec<Location>> { ls.iter().map(|l| shift(l)).collect() };
+    let range = |a: i64, b: i64, before, after| {
+        Range((a + offset, Before(before)), (b + offset, After(after)))
+    };
+    Some(match *l {
+        Range((a, Before(before)), (b, After(after))) => {
+            if circular && a >= len {
+                range(a - len, b - len, before, after)
+            } else if circular && b > len {
+                Join(vec![
+                    range(a, len, before, false),
+                    range(0, b - len, false, after),
+                ])
+            } else {
+                range(a, b, before, after)
+            }
+        }
+        // the fragment is opened at its origin unless it is all of a
+        // circular product
+        Between(a, _) if circular && a == len => {
+            if circular_product && len == total {
+                Between(total, 1)
+            } else {
+                return None;
+            }
+        }
+        Between(a, _) => {
+            let a = a + offset;
+            if a < total {
+                Between(a, a + 1)
+            } else if circular_product {
+                Between(total, 1)
+            } else {
+                return None;
+            }
+        }
+        Complement(ref l) => Complement(Box::new(shift(l)?)),
+        Join(ref ls) => Join(list(ls)?),
+        Order(ref ls) => Order(list(ls)?),
+        Bond(ref ls) => Bond(list(ls)?),
+        OneOf(ref ls) => OneOf(list(ls)?),
+        External(..) | Gap(..) => l.clone(),
+    })
+}
+
+fn fragment_name(seq: &Seq, idx: usize) -> String {
+    seq.name
+        .clone()
+        .unwrap_or_else(|| format!("fragment {}", idx + 1))
+}
+
+pub fn concatenate(seqs: &[&Seq], settings: &ConcatSettings) -> Result<Seq, String> {
+    if seqs.is_empty() {
+        return Err("No sequences to concatenate".into());
+    }
+    if settings.reverse.len() > seqs.len() {
+        return Err(format!(
+            "{} orientation flags given for {} sequences",
+            settings.reverse.len(),
+            seqs.len()
+        ));
+    }
+    let fragments: Vec<Seq> = seqs
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            if settings.reverse.get(i).cloned().unwrap_or(false) {
+                s.revcomp()
+            } else {
+                (*s).clone()
+            }
+        })
+        .collect();
+    let total: i64 = fragments.iter().map(|f| f.len()).sum();
+    let mut bases = Vec::with_capacity(total as usize);
+    let mut features = Vec::new();
+    let mut junctions = Vec::new();
+    for (i, f) in fragments.iter().enumerate() {
+        let offset = bases.len() as i64;
+        for feature in &f.features {
+            if let Some(location) = shift_location(
+                &feature.location,
+                offset,
+                f.len(),
+                f.is_circular(),
+                total,
+                settings.circular,
+            ) {
+                features.push(Feature {
+                    location,
+                    ..feature.clone()
+                });
+            }
+        }
+        bases.extend_from_slice(&f.seq);
+        let next = if i + 1 < fragments.len() {
+            Some(i + 1)
+        } else if settings.circular {
+            Some(0)
+        } else {
+            None
+        };
+        if let Some(next) = next {
+            junctions.push((
+                bases.len() as i64,
+                fragment_name(f, i),
+                fragment_name(&fragments[next], next),
+            ));
+        }
+    }
+    if settings.junctions {
+        for (pos, left, right) in junctions {
+            let location = if pos == total {
+                Location::Between(total, 1)
+            } else {
+                Location::Between(pos, pos + 1)
+            };
+            features.push(Feature {
+                kind: feature_kind!("misc_feature"),
+                location,
+                qualifiers: vec![
+                    (qualifier_key!("label"), Some(format!("{}/{}", left, right))),
+                    (
+                        qualifier_key!("note"),
+                        Some(format!("junction between {} and {}", left, right)),
+                    ),
+                ],
+            });
+        }
+    }
+    Ok(Seq {
+        name: Some(
+            fragments
+                .iter()
+                .enumerate()
+                .map(|(i, f)| fragment_name(f, i))
+                .collect::<Vec<_>>()
+                .join("+"),
+        ),
+        topology: if settings.circular {
+            Topology::Circular
+        } else {
+            Topology::Linear
+        },
+        molecule_type: fragments[0].molecule_type.clone(),
+        seq: bases,
+        features,
+        ..Seq::empty()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    fn seq(name: &str, bases: &[u8], circular: bool, locations: &[&str]) -> Seq {
+        Seq {
+            name: Some(name.into()),
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            seq: bases.to_vec(),
+            features: locations
+                .iter()
+                .map(|l| Feature {
+                    kind: feature_kind!("misc_feature"),
+                    location: location::parse(l).unwrap(),
+                    qualifiers: Vec::new(),
+                })
+                .collect(),
+            ..Seq::empty()
+        }
+    }
+
+    fn locations(seq: &Seq) -> Vec<String> {
+        seq.features
+            .iter()
+            .map(|f| location::format(&f.location))
+            .collect()
+    }
+
+    #[test]
+    fn test_concatenate() {
+        let a = seq("a", b"aaaaa", false, &["2..4"]);
+        let b = seq("b", b"ccggg", true, &["join(5,1..2)", "3^4"]);
+        let settings = ConcatSettings {
+            reverse: vec![false, true],
+            circular: false,
+            junctions: true,
+        };
+        let res = concatenate(&[&a, &b], &settings).unwrap();
+        assert_eq!(res.seq, b"aaaaacccgg".to_vec());
+        assert_eq!(res.name.as_ref().unwrap(), "a+b");
+        assert!(!res.is_circular());
+        assert_eq!(res.features.len(), 4);
+        assert_eq!(locations(&res)[3], "5^6");
+        assert_eq!(
+            res.features[3]
+                .qualifier_values(qualifier_key!("label"))
+                .next(),
+            Some("a/b")
+        );
+        let settings = ConcatSettings {
+            circular: true,
+            junctions: true,
+            ..ConcatSettings::default()
+        };
+        let res = concatenate(&[&a, &b], &settings).unwrap();
+        assert!(res.is_circular());
+        assert_eq!(
+            locations(&res),
+            vec!["2..4", "join(10,6..7)", "8^9", "5^6", "10^1"]
+        );
+        assert!(concatenate(&[], &settings).is_err());
+        let settings = ConcatSettings {
+            reverse: vec![true; 3],
+            ..ConcatSettings::default()
+        };
+        assert!(concatenate(&[&a, &b], &settings).is_err());
+    }
+
+    #[test]
+    fn test_origin() {
+        let a = seq("a", b"acgt", true, &["3..6"]);
+        let res = concatenate(&[&a, &a], &ConcatSettings::default()).unwrap();
+        assert_eq!(res.seq, b"acgtacgt".to_vec());
+        assert_eq!(locations(&res), vec!["join(3..4,1..2)", "join(7..8,5..6)"]);
+        // a site at the origin is gone once the fragment is opened
+        let c = seq("c", b"acgt", true, &["4^1", "2^3"]);
+        let res = concatenate(&[&c, &c], &ConcatSettings::default()).unwrap();
+        assert_eq!(locations(&res), vec!["2^3", "6^7"]);
+        let settings = ConcatSettings {
+            circular: true,
+            ..ConcatSettings::default()
+        };
+        let res = concatenate(&[&c], &settings).unwrap();
+        assert_eq!(locations(&res), vec!["4^1", "2^3"]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::archive;
+use crate::bin_format;
+use crate::project;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    GenBank,
+    Fasta,
+    Embl,
+    Json,
+    SnapGene,
+    Bin,
+    Project,
+    Gzip,
+    Zip,
+}
+
+/// Guess the format of a file from its contents
+pub fn detect(data: &[u8]) -> Option<Format> {
+    if archive::is_gzip(data) {
+        return Some(Format::Gzip);
+    }
+    if data.starts_with(b"PK\x03\x04") {
+        return Some(Format::Zip);
+    }
+    if project::is_project(data) {
+        return Some(Format::Project);
+    }
+    if data.len() >= 13 && data[0] == 0x09 && &data[5..13] == b"SnapGene" {
+        return Some(Format::SnapGene);
+    }
+    // the binary format only starts with a version tag, so make sure that
+    // the rest decodes too
+    if bin_format::decode(data).is_ok() {
+        return Some(Format::Bin);
+    }
+    // skip a UTF-8 byte order mark
+    let text = if data.starts_with(b"\xef\xbb\xbf") {
+        &data[3..]
+    } else {
+        data
+    };
+    let start = text
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    let text = &text[start..];
+    if text.starts_with(b"LOCUS") {
+        Some(Format::GenBank)
+    } else if text.starts_with(b">") {
+        Some(Format::Fasta)
+    } else if text.starts_with(b"ID   ") {
+        Some(Format::Embl)
+    } else if text.starts_with(b"{") || text.starts_with(b"[") {
+        Some(Format::Json)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect(b"\n\nLOCUS       test"), Some(Format::GenBank));
+        assert_eq!(detect(b"\xef\xbb\xbf>test\nACGT"), Some(Format::Fasta));
+        assert_eq!(detect(b"ID   X56734; SV 1;"), Some(Format::Embl));
+        assert_eq!(
+            detect(b"\x09\x00\x00\x00\x0eSnapGene\x00\x01"),
+            Some(Format::SnapGene)
+        );
+        let bin = bin_format::encode(&gb_io::seq::Seq::empty()).unwrap();
+        assert_eq!(detect(&bin), Some(Format::Bin));
+        assert_eq!(detect(b"\x03\x00\x00\x00\x00"), None);
+        assert_eq!(
+            detect(b"CLNPROJ\x00\x01\x00\x00\x00"),
+            Some(Format::Project)
+        );
+        assert_eq!(detect(b"\x1f\x8b\x08\x00"), Some(Format::Gzip));
+        assert_eq!(detect(b"PK\x03\x04"), Some(Format::Zip));
+        assert_eq!(detect(b" {\"version\": 1"), Some(Format::Json));
+        assert_eq!(detect(b"hello"), None);
+    }
+}
@@ -0,0 +1,432 @@
+//! Differences between a reference (e.g. a designed plasmid) and a sample
+//! (e.g. its sequencing result). For circular sequences the sample is first
+//! rotated, and if needed reverse complemented, to line up with the
+//! reference using shared k-mers, then the two are aligned globally within a
+//! band around the diagonal, widening it until the alignment is optimal.
+use crate::location::for_each_range;
+use bio::alphabets::dna;
+use gb_io::seq::*;
+use std::collections::HashMap;
+
+const K: usize = 16;
+/// Maximum number of k-mers of the reference used to find the rotation
+const MAX_SAMPLES: usize = 2000;
+/// Limit on the size of the traceback matrix, about 64MB
+const MAX_CELLS: usize = 1 << 26;
+
+const MISMATCH: u32 = 1;
+const GAP_OPEN: u32 = 2;
+const GAP_EXTEND: u32 = 1;
+const INF: u32 = u32::max_value() / 2;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Substitution,
+    /// bases in the sample which aren't in the reference
+    Insertion,
+    /// bases in the reference which aren't in the sample
+    Deletion,
+}
+
+/// Coordinates are 0-based, `end` exclusive. Sample coordinates refer to the
+/// sample as given, `sample_end` may be past its end if the change crosses
+/// the origin. `sample_bases` are on the reference strand.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub ref_start: i64,
+    pub ref_end: i64,
+    pub sample_start: i64,
+    pub sample_end: i64,
+    pub ref_bases: String,
+    pub sample_bases: String,
+    /// indices of the features of the reference the change falls in
+    pub ref_features: Vec<usize>,
+    pub sample_features: Vec<usize>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Diff {
+    /// whether the sample was reverse complemented to align it
+    pub sample_reversed: bool,
+    /// position of the sample (after reverse complementing) aligned to the
+    /// start of the reference
+    pub sample_offset: i64,
+    pub identity: f64,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Op {
+    Match,
+    Subst,
+    /// consumes a base of the reference only
+    Del,
+    /// consumes a base of the sample only
+    Ins,
+}
+
+/// Find the orientation and rotation of `b` which best lines up with `a`,
+/// by voting with the k-mers they share. Returns `(reversed, offset)`.
+fn orient(a: &[u8], b: &[u8], rotate: bool) -> (bool, usize) {
+    let k = K.min(a.len()).min(b.len());
+    if k == 0 {
+        return (false, 0);
+    }
+    let step = (a.len() / MAX_SAMPLES).max(1);
+    let mut best = (0, false, 0);
+    for &reversed in &[false, true] {
+        let b = if reversed {
+            dna::revcomp(b)
+        } else {
+            b.to_vec()
+        };
+        // wrap around for circular sequences
+        let mut bb = b.clone();
+        if rotate {
+            bb.extend_from_slice(&b[..k - 1]);
+        }
+        let mut kmers: HashMap<&[u8], Option<usize>> = HashMap::new();
+        for j in 0..=bb.len() - k {
+            kmers
+                .entry(&bb[j..j + k])
+                .and_modify(|e| *e = None)
+                .or_insert(Some(j));
+        }
+        let mut votes: HashMap<usize, usize> = HashMap::new();
+        for i in (0..=a.len() - k).step_by(step) {
+            if let Some(&Some(j)) = kmers.get(&a[i..i + k]) {
+                let offset = if rotate {
+                    (j + b.len() - i % b.len()) % b.len()
+                } else {
+                    0
+                };
+                *votes.entry(offset).or_insert(0) += 1;
+            }
+        }
+        if let Some((&offset, &n)) = votes.iter().max_by_key(|&(&o, &n)| (n, -(o as i64))) {
+            if n > best.0 {
+                best = (n, reversed, offset);
+            }
+        }
+    }
+    (best.1, best.2)
+}
+
+/// Global alignment with affine gap costs, only considering cells whose
+/// diagonal `j - i` lies within `w` of those between the two corners.
+/// Returns `None` if the band needs to be wider to be sure the alignment is
+/// optimal, and an error if it is too big.
+fn align_banded(a: &[u8], b: &[u8], w: usize) -> Result<Option<(u32, Vec<Op>)>, String> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let w = w as isize;
+    let dmin = 0isize.min(m - n) - w;
+    let dmax = 0isize.max(m - n) + w;
+    let width = (dmax - dmin + 1) as usize;
+    if (n as usize + 1).saturating_mul(width) > MAX_CELLS {
+        return Err("Sequences are too long to compare".into());
+    }
+    // traceback: the previous state for each of M, X and Y in 2 bits each,
+    // 0 = M (diagonal), 1 = X (Del), 2 = Y (Ins)
+    let mut trace = vec![0u8; (n as usize + 1) * width];
+    let col = |i: isize, j: isize| (j - i - dmin) as usize;
+    let mut prev = vec![[INF; 3]; width];
+    let mut cur = vec![[INF; 3]; width];
+    let min_state = |c: &[u32; 3], extra: [u32; 3]| -> (u32, u8) {
+        (0..3)
+            .map(|s| (c[s].saturating_add(extra[s]), s as u8))
+            .min()
+            .unwrap()
+    };
+    for i in 0..=n {
+        for c in cur.iter_mut() {
+            *c = [INF; 3];
+        }
+        let lo = 0isize.max(i + dmin);
+        let hi = m.min(i + dmax);
+        for j in lo..=hi {
+            let k = col(i, j);
+            let mut cell = [INF; 3];
+            let mut ptr = 0u8;
+            if i == 0 && j == 0 {
+                cell[0] = 0;
+            }
+            if i > 0 && j > 0 {
+                // diagonal, same column in the previous row
+                let cost = if a[i as usize - 1] == b[j as usize - 1] {
+                    0
+                } else {
+                    MISMATCH
+                };
+                let (v, s) = min_state(&prev[k], [0, 0, 0]);
+                cell[0] = v.saturating_add(cost);
+                ptr |= s;
+            }
+            if i > 0 && k + 1 < width {
+                let open = GAP_OPEN + GAP_EXTEND;
+                let (v, s) = min_state(&prev[k + 1], [open, GAP_EXTEND, open]);
+                cell[1] = v;
+                ptr |= s << 2;
+            }
+            if j > 0 && k > 0 {
+                let open = GAP_OPEN + GAP_EXTEND;
+                let (v, s) = min_state(&cur[k - 1], [open, open, GAP_EXTEND]);
+                cell[2] = v;
+                ptr |= s << 4;
+            }
+            cur[k] = [cell[0].min(INF), cell[1].min(INF), cell[2].min(INF)];
+            trace[i as usize * width + k] = ptr;
+        }
+        if i < n {
+            std::mem::swap(&mut prev, &mut cur);
+        }
+    }
+    let (cost, mut state) = min_state(&cur[col(n, m)], [0, 0, 0]);
+    // a path leaving the band has to make up at least `w` gaps both ways
+    if cost >= 2 * w as u32 * GAP_EXTEND && w < n.max(m) {
+        return Ok(None);
+    }
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        let ptr = trace[i as usize * width + col(i, j)];
+        let next = (ptr >> (2 * state)) & 3;
+        match state {
+            0 => {
+                i -= 1;
+                j -= 1;
+                ops.push(if a[i as usize] == b[j as usize] {
+                    Op::Match
+                } else {
+                    Op::Subst
+                });
+            }
+            1 => {
+                i -= 1;
+                ops.push(Op::Del);
+            }
+            _ => {
+                j -= 1;
+                ops.push(Op::Ins);
+            }
+        }
+        state = next;
+    }
+    ops.reverse();
+    Ok(Some((cost, ops)))
+}
+
+/// Align `a` globally to `b`, widening the band as needed
+pub(crate) fn align(a: &[u8], b: &[u8]) -> Result<Vec<Op>, String> {
+    let mut w = 32;
+    loop {
+        match align_banded(a, b, w)? {
+            Some((_, ops)) => return Ok(ops),
+            None if w < a.len().max(b.len()) => w *= 2,
+            None => return Err("Sequences are too different to compare".into()),
+        }
+    }
+}
+
+/// Indices of the features of `seq` overlapping `start..end`, or containing
+/// the position between two bases if it is empty
+fn features_at(seq: &Seq, start: i64, end: i64) -> Vec<usize> {
+    let len = seq.len();
+    let shifts: &[i64] = if seq.is_circular() {
+        &[-len, 0, len]
+    } else {
+        &[0]
+    };
+    let mut res = Vec::new();
+    for (idx, f) in seq.features.iter().enumerate() {
+        let mut hit = false;
+        for_each_range(
+            &f.location,
+            &mut |a, b, _| {
+                for shift in shifts {
+                    let (a, b) = (a + shift, b + shift);
+                    if (start == end && a < start && start < b)
+                        || (start < end && a < end && start < b)
+                    {
+                        hit = true;
+                    }
+                }
+            },
+            true,
+        );
+        if hit {
+            res.push(idx);
+        }
+    }
+    res
+}
+
+pub fn diff(reference: &Seq, sample: &Seq) -> Result<Diff, String> {
+    let a = reference.seq.to_ascii_uppercase();
+    let b = sample.seq.to_ascii_uppercase();
+    let circular = reference.is_circular() && sample.is_circular();
+    let (reversed, offset) = orient(&a, &b, circular);
+    let mut b = if reversed { dna::revcomp(&b) } else { b };
+    b.rotate_left(offset);
+    let ops = align(&a, &b)?;
+
+    let n = b.len() as i64;
+    // map a range of the rotated sample back to the original
+    let map = |start: i64, end: i64| -> (i64, i64) {
+        if n == 0 {
+            return (0, 0);
+        }
+        let len = end - start;
+        let s = (start + offset as i64) % n;
+        let s = if reversed {
+            (n - s - len).rem_euclid(n)
+        } else {
+            s
+        };
+        (s, s + len)
+    };
+
+    let mut changes = Vec::new();
+    let mut matches = 0;
+    let (mut i, mut j) = (0, 0);
+    let mut idx = 0;
+    while idx < ops.len() {
+        let op = ops[idx];
+        if op == Op::Match {
+            matches += 1;
+            i += 1;
+            j += 1;
+            idx += 1;
+            continue;
+        }
+        let run = ops[idx..].iter().take_while(|&&o| o == op).count();
+        let (di, dj) = match op {
+            Op::Subst => (run, run),
+            Op::Del => (run, 0),
+            _ => (0, run),
+        };
+        let (ref_start, ref_end) = (i as i64, (i + di) as i64);
+        let (sample_start, sample_end) = map(j as i64, (j + dj) as i64);
+        changes.push(Change {
+            kind: match op {
+                Op::Subst => ChangeKind::Substitution,
+                Op::Del => ChangeKind::Deletion,
+                _ => ChangeKind::Insertion,
+            },
+            ref_start,
+            ref_end,
+            sample_start,
+            sample_end,
+            ref_bases: String::from_utf8_lossy(&a[i..i + di]).into(),
+            sample_bases: String::from_utf8_lossy(&b[j..j + dj]).into(),
+            ref_features: features_at(reference, ref_start, ref_end),
+            sample_features: features_at(sample, sample_start, sample_end),
+        });
+        i += di;
+        j += dj;
+        idx += run;
+    }
+    Ok(Diff {
+        sample_reversed: reversed,
+        sample_offset: offset as i64,
+        identity: if ops.is_empty() {
+            1.0
+        } else {
+            matches as f64 / ops.len() as f64
+        },
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    const REF: &[u8] = b"ATGACCATGATTACGGATTCACTGGCCGTCGTTTTACAACGTCGTGACTGGGAAAACCCTGGCG";
+
+    fn seq(bases: &[u8], circular: bool, locations: &[&str]) -> Seq {
+        Seq {
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            seq: bases.to_vec(),
+            features: locations
+                .iter()
+                .map(|l| Feature {
+                    kind: feature_kind!("misc_feature"),
+                    location: location::parse(l).unwrap(),
+                    qualifiers: Vec::new(),
+                })
+                .collect(),
+            ..Seq::empty()
+        }
+    }
+
+    #[test]
+    fn test_align() {
+        let ops = align(b"ACGTACGT", b"ACGTTACGA").unwrap();
+        assert_eq!(ops.iter().filter(|&&o| o == Op::Ins).count(), 1);
+        assert_eq!(ops.iter().filter(|&&o| o == Op::Subst).count(), 1);
+        assert_eq!(ops.len(), 9);
+        assert!(align(b"", b"").unwrap().is_empty());
+        assert_eq!(align(b"AC", b"").unwrap(), vec![Op::Del, Op::Del]);
+        let long = vec![b'A'; 100_000];
+        assert_eq!(
+            align(&long[..1000], &long),
+            Err("Sequences are too long to compare".into())
+        );
+    }
+
+    #[test]
+    fn test_linear() {
+        let reference = seq(REF, false, &["1..12", "20..30"]);
+        let mut bases = REF.to_vec();
+        bases[5] = b'G';
+        bases.drain(20..23);
+        bases.insert(40, b'T');
+        let sample = seq(&bases, false, &[]);
+        let res = diff(&reference, &sample).unwrap();
+        assert!(!res.sample_reversed);
+        assert_eq!(res.changes.len(), 3);
+        let c = &res.changes[0];
+        assert_eq!(c.kind, ChangeKind::Substitution);
+        assert_eq!((c.ref_start, c.ref_end, c.sample_start), (5, 6, 5));
+        assert_eq!((c.ref_bases.as_str(), c.sample_bases.as_str()), ("C", "G"));
+        assert_eq!(c.ref_features, vec![0]);
+        let c = &res.changes[1];
+        assert_eq!(c.kind, ChangeKind::Deletion);
+        assert_eq!(c.ref_end - c.ref_start, 3);
+        assert_eq!(c.sample_start, c.sample_end);
+        assert_eq!(c.ref_features, vec![1]);
+        let c = &res.changes[2];
+        assert_eq!(c.kind, ChangeKind::Insertion);
+        assert_eq!(c.sample_bases, "T");
+    }
+
+    #[test]
+    fn test_circular() {
+        let reference = seq(REF, true, &[]);
+        let mut bases = REF.to_vec();
+        bases[30] = b'A';
+        bases.rotate_left(25);
+        let bases = dna::revcomp(&bases);
+        let sample = seq(&bases, true, &["1..10"]);
+        let res = diff(&reference, &sample).unwrap();
+        assert!(res.sample_reversed);
+        assert_eq!(res.changes.len(), 1);
+        let c = &res.changes[0];
+        assert_eq!((c.ref_start, c.ref_end), (30, 31));
+        assert_eq!(c.sample_bases, "A");
+        // position 30 of the reference is at 5 after rotating, and at
+        // len - 6 after reverse complementing
+        let n = REF.len() as i64;
+        assert_eq!((c.sample_start, c.sample_end), (n - 6, n - 5));
+        assert!(c.sample_features.is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+//! Editing the bases of a sequence. Features are shifted to follow the bases
+//! they cover, truncated if some of them are deleted, and dropped if all of
+//! them are.
+use crate::fasta::is_iupac;
+use gb_io::seq::*;
+
+/// How the coordinates change: `range` maps a `(start, end)` pair, `boundary`
+/// maps a position between two bases (as used by `Between`), `None` meaning
+/// it was deleted
+struct Mapping<R, B> {
+    range: R,
+    boundary: B,
+}
+
+fn map_location<R, B>(l: &Location, m: &Mapping<R, B>) -> Option<Location>
+where
+    R: Fn(i64, i64) -> Option<(i64, i64)>,
+    B: Fn(i64) -> Option<i64>,
+{
+    use Location::*;
+    let list = |ls: &[Location]| -> Vec<Location> {
+        ls.iter().filter_map(|l| map_location(l, m)).collect()
+    };
+    // a list left with a single location doesn't need the wrapper any more
+    let collapse = |mut ls: Vec<Location>, wrap: fn(Vec<Location>) -> Location| match ls.len() {
+        0 => None,
+        1 => ls.pop(),
+        _ => Some(wrap(ls)),
+    };
+    match *l {
+        Range((a, Before(before)), (b, After(after))) => {
+            let (a, b) = (m.range)(a, b)?;
+            Some(Range((a, Before(before)), (b, After(after))))
+        }
+        Between(a, b) => {
+            let new_a = (m.boundary)(a)?;
+            // `b` is usually `a + 1`, but may be 1 if `a` is the end of a
+            // circular sequence
+            let new_b = if b == a + 1 { new_a + 1 } else { b };
+            Some(Between(new_a, new_b))
+        }
+        Complement(ref l) => map_location(l, m).map(|l| Complement(Box::new(l))),
+        Join(ref ls) => collapse(list(ls), Join),
+        Order(ref ls) => collapse(list(ls), Order),
+        Bond(ref ls) => collapse(list(ls), Bond),
+        OneOf(ref ls) => collapse(list(ls), OneOf),
+        External(..) | Gap(..) => Some(l.clone()),
+    }
+}
+
+fn map_features<R, B>(seq: &Seq, new_len: i64, m: Mapping<R, B>) -> Vec<Feature>
+where
+    R: Fn(i64, i64) -> Option<(i64, i64)>,
+    B: Fn(i64) -> Option<i64>,
+{
+    let len = seq.len();
+    let circular = seq.is_circular();
+    // ranges of a circular sequence may extend past the end
+    let wrapped = |x: i64, f: &dyn Fn(i64) -> Option<i64>| {
+        if circular && x > len {
+            f(x - len).map(|x| x + new_len)
+        } else {
+            f(x)
+        }
+    };
+    let m = Mapping {
+        range: |a: i64, b: i64| -> Option<(i64, i64)> {
+            if circular && a >= len {
+                (m.range)(a - len, b - len).map(|(a, b)| (a + new_len, b + new_len))
+            } else if circular && b > len {
+                // map both halves separately
+                let (a, _) = (m.range)(a, len).unwrap_or((len, len));
+                let (_, b) = (m.range)(0, b - len).unwrap_or((0, 0));
+                let b = b + new_len;
+                if a < b {
+                    Some((a, b))
+                } else {
+                    None
+                }
+            } else {
+                (m.range)(a, b)
+            }
+        },
+        boundary: |x: i64| wrapped(x, &m.boundary),
+    };
+    seq.features
+        .iter()
+        .filter_map(|f| {
+            Some(Feature {
+                location: map_location(&f.location, &m)?,
+                ..f.clone()
+            })
+        })
+        .collect()
+}
+
+fn check_bases(bases: &[u8]) -> Result<(), String> {
+    match bases.iter().find(|&&c| !is_iupac(c)) {
+        Some(&c) => Err(format!("Invalid base: {:?}", c as char)),
+        None => Ok(()),
+    }
+}
+
+fn with_seq(seq: &Seq, bases: Vec<u8>, features: Vec<Feature>) -> Seq {
+    Seq {
+        len: seq.len.map(|_| bases.len()),
+        seq: bases,
+        features,
+        ..seq.clone()
+    }
+}
+
+/// Insert `bases` before position `pos` (0-based)
+pub fn insert(seq: &Seq, pos: i64, bases: &[u8]) -> Result<Seq, String> {
+    if pos < 0 || pos > seq.len() {
+        return Err(format!("Position out of range: {}", pos));
+    }
+    check_bases(bases)?;
+    let n = bases.len() as i64;
+    let mut new_seq = seq.seq.clone();
+    new_seq.splice(pos as usize..pos as usize, bases.iter().cloned());
+    let features = map_features(
+        seq,
+        seq.len() + n,
+        Mapping {
+            // a feature containing `pos` grows, one starting at `pos` moves
+            range: |a, b| {
+                Some((
+                    if a >= pos { a + n } else { a },
+                    if b > pos { b + n } else { b },
+                ))
+            },
+            boundary: |x| Some(if x > pos { x + n } else { x }),
+        },
+    );
+    Ok(with_seq(seq, new_seq, features))
+}
+
+fn delete_linear(seq: &Seq, start: i64, end: i64) -> Seq {
+    let d = end - start;
+    let mut new_seq = seq.seq.clone();
+    new_seq.drain(start as usize..end as usize);
+    let pos = |x: i64| {
+        if x <= start {
+            x
+        } else if x >= end {
+            x - d
+        } else {
+            start
+        }
+    };
+    let features = map_features(
+        seq,
+        seq.len() - d,
+        Mapping {
+            range: |a, b| {
+                let (a, b) = (pos(a), pos(b));
+                if a < b {
+                    Some((a, b))
+                } else {
+                    None
+                }
+            },
+            boundary: |x| {
+                if x > start && x < end {
+                    None
+                } else {
+                    Some(pos(x))
+                }
+            },
+        },
+    );
+    with_seq(seq, new_seq, features)
+}
+
+/// Delete the bases `start..end` (0-based). For circular sequences `end` may
+/// be past the end of the sequence, to delete across the origin.
+pub fn delete(seq: &Seq, start: i64, end: i64) -> Result<Seq, String> {
+    let len = seq.len();
+    let max_end = if seq.is_circular() { start + len } else { len };
+    if start < 0 || start > len || end < start || end > max_end {
+        return Err(format!("Range out of bounds: {}..{}", start, end));
+    }
+    if end > len {
+        let res = delete_linear(seq, start, len);
+        Ok(delete_linear(&res, 0, end - len))
+    } else {
+        Ok(delete_linear(seq, start, end))
+    }
+}
+
+/// Replace the bases `start..end` with `bases`, see `delete`
+pub fn replace(seq: &Seq, start: i64, end: i64, bases: &[u8]) -> Result<Seq, String> {
+    check_bases(bases)?;
+    let res = delete(seq, start, end)?;
+    let pos = if end > seq.len() { res.len() } else { start };
+    insert(&res, pos, bases)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    fn seq(circular: bool, locations: &[&str]) -> Seq {
+        Seq {
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            seq: b"aaaaaccccc".to_vec(),
+            features: locations
+                .iter()
+                .map(|l| Feature {
+                    kind: feature_kind!("misc_feature"),
+                    location: location::parse(l).unwrap(),
+                    qualifiers: Vec::new(),
+                })
+                .collect(),
+            ..Seq::empty()
+        }
+    }
+
+    fn locations(seq: &Seq) -> Vec<String> {
+        seq.features
+            .iter()
+            .map(|f| location::format(&f.location))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let s = seq(
+            false,
+            &[
+                "1..5",
+                "6..10",
+                "3..8",
+                "complement(join(1..2,7..8))",
+                "5^6",
+            ],
+        );
+        let res = insert(&s, 5, b"gg").unwrap();
+        assert_eq!(res.seq, b"aaaaaggccccc".to_vec());
+        assert_eq!(
+            locations(&res),
+            vec![
+                "1..5",
+                "8..12",
+                "3..10",
+                "complement(join(1..2,9..10))",
+                "5^6"
+            ]
+        );
+        assert!(insert(&s, 11, b"a").is_err());
+        assert!(insert(&s, 0, b"a1").is_err());
+    }
+
+    #[test]
+    fn test_delete() {
+        let s = seq(
+            false,
+            &["1..5", "6..10", "3..8", "join(1..2,4..5,7..8)", "4..5"],
+        );
+        let res = delete(&s, 3, 6).unwrap();
+        assert_eq!(res.seq, b"aaacccc".to_vec());
+        assert_eq!(
+            locations(&res),
+            vec!["1..3", "4..7", "3..5", "join(1..2,4..5)"]
+        );
+    }
+
+    #[test]
+    fn test_circular() {
+        let s = seq(true, &["join(9..10,1..2)", "3..4", "10"]);
+        // delete across the origin
+        let res = delete(&s, 9, 11).unwrap();
+        assert_eq!(res.seq, b"aaaacccc".to_vec());
+        assert_eq!(locations(&res), vec!["join(8,1)", "2..3"]);
+        let res = replace(&s, 9, 11, b"ttt").unwrap();
+        assert_eq!(res.seq, b"aaaaccccttt".to_vec());
+        // inserting at the origin doesn't change features spanning it
+        let res = insert(&s, 10, b"g").unwrap();
+        assert_eq!(locations(&res), vec!["join(9..10,1..2)", "3..4", "10"]);
+    }
+}
@@ -0,0 +1,494 @@
+use gb_io::seq::*;
+use std::io::{self, Write};
+use std::str;
+
+use crate::location;
+
+// qualifiers whose values are written without quotes
+const UNQUOTED: &[&str] = &[
+    "anticodon",
+    "citation",
+    "codon_start",
+    "compare",
+    "direction",
+    "estimated_length",
+    "mod_base",
+    "number",
+    "rpt_type",
+    "rpt_unit_range",
+    "tag_peptide",
+    "transl_except",
+    "transl_table",
+];
+
+// qualifiers whose values are continued without a space, all others are
+// joined with one
+const NO_SPACE: &[&str] = &["translation"];
+
+const LINE_WIDTH: usize = 80;
+// width of the `FT   key             ` prefix
+const FT_INDENT: usize = 21;
+
+/// Parse all records in an EMBL flat file
+pub fn parse(data: &[u8]) -> Result<Vec<Seq>, String> {
+    let text = str::from_utf8(data).map_err(|e| format!("Invalid UTF-8: {}", e))?;
+    let mut res = Vec::new();
+    let mut record = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.starts_with("//") {
+            res.push(parse_record(&record).map_err(|e| format!("Line {}: {}", i + 1, e))?);
+            record.clear();
+        } else if !line.is_empty() {
+            record.push(line);
+        }
+    }
+    if record.iter().any(|l| !l.starts_with("XX")) {
+        return Err("Unterminated record".into());
+    }
+    Ok(res)
+}
+
+fn parse_record(lines: &[&str]) -> Result<Seq, String> {
+    let mut seq = Seq::empty();
+    let mut feature_lines = Vec::new();
+    let mut definition = Vec::new();
+    let mut keywords = Vec::new();
+    let mut organism: Vec<&str> = Vec::new();
+    let mut comment = Vec::new();
+    let mut in_seq = false;
+    for &line in lines {
+        if in_seq {
+            seq.seq
+                .extend(line.bytes().filter(|c| c.is_ascii_alphabetic()));
+            continue;
+        }
+        let code = line.get(..2).unwrap_or(line);
+        let content = line.get(5..).unwrap_or("");
+        match code {
+            "ID" => parse_id(content, &mut seq)?,
+            "AC" => {
+                if seq.accession.is_none() {
+                    seq.accession = content.split(';').next().map(|a| a.trim().to_string());
+                }
+            }
+            "DE" => definition.push(content.trim()),
+            "KW" => keywords.push(content.trim().trim_end_matches('.')),
+            "OS" | "OC" => organism.push(content.trim()),
+            "CC" => comment.push(content.trim_end()),
+            "FT" => feature_lines.push(line),
+            "SQ" => in_seq = true,
+            _ => {}
+        }
+    }
+    if !definition.is_empty() {
+        seq.definition = Some(definition.join(" "));
+    }
+    if keywords.iter().any(|k| !k.is_empty()) {
+        seq.keywords = Some(keywords.join(" "));
+    }
+    if let Some(&os) = organism.first() {
+        seq.source = Some(Source {
+            source: os.to_string(),
+            organism: Some(organism.join("\n")),
+        });
+    }
+    if !comment.is_empty() {
+        seq.comments.push(comment.join("\n"));
+    }
+    seq.features = parse_features(&feature_lines)?;
+    Ok(seq)
+}
+
+/// `ID   X56734; SV 1; linear; mRNA; STD; PLN; 1859 BP.`
+fn parse_id(content: &str, seq: &mut Seq) -> Result<(), String> {
+    let fields: Vec<_> = content.split(';').map(str::trim).collect();
+    if fields.len() < 7 {
+        // old style (pre release 87) ID line, e.g. `X56734 standard; DNA; PLN; 1859 BP.`
+        seq.name = fields[0].split_whitespace().next().map(String::from);
+        return Ok(());
+    }
+    seq.name = Some(fields[0].to_string());
+    if fields[1].starts_with("SV ") {
+        seq.version = Some(format!("{}.{}", fields[0], &fields[1][3..]));
+    }
+    seq.topology = match fields[2] {
+        "circular" => Topology::Circular,
+        "linear" => Topology::Linear,
+        t => return Err(format!("Unknown topology: {}", t)),
+    };
+    seq.molecule_type = Some(match fields[3] {
+        "genomic DNA" | "other DNA" | "unassigned DNA" => "DNA".into(),
+        "genomic RNA" | "other RNA" | "unassigned RNA" => "RNA".into(),
+        t => t.into(),
+    });
+    seq.division = fields[5].to_string();
+    Ok(())
+}
+
+/// Parse the FT block into features, `lines` still include the `FT` prefix
+fn parse_features(lines: &[&str]) -> Result<Vec<Feature>, String> {
+    // (kind, location, qualifiers)
+    let mut raw: Vec<(&str, String, Vec<String>)> = Vec::new();
+    for line in lines {
+        let key = line.get(5..FT_INDENT).unwrap_or("").trim();
+        let content = line.get(FT_INDENT..).unwrap_or("").trim_end();
+        if !key.is_empty() {
+            raw.push((key, content.to_string(), Vec::new()));
+            continue;
+        }
+        let (_, location, qualifiers) = raw
+            .last_mut()
+            .ok_or_else(|| String::from("Feature table doesn't start with a key"))?;
+        // an unbalanced quote means that the value continues on this line
+        let continued = qualifiers
+            .last()
+            .map(|q| q.matches('"').count() % 2 == 1)
+            .unwrap_or(false);
+        if continued {
+            let q = qualifiers.last_mut().unwrap();
+            if !NO_SPACE.contains(&qualifier_key(q)) {
+                q.push(' ');
+            }
+            q.push_str(content);
+        } else if content.starts_with('/') {
+            qualifiers.push(content.to_string());
+        } else if qualifiers.is_empty() {
+            location.push_str(content);
+        } else {
+            return Err(format!("Unexpected line in feature table: {}", line));
+        }
+    }
+    raw.into_iter()
+        .map(|(kind, location, qualifiers)| {
+            Ok(Feature {
+                kind: FeatureKind::from(kind),
+                location: location::parse(&location)?,
+                qualifiers: qualifiers.iter().map(|q| parse_qualifier(q)).collect(),
+            })
+        })
+        .collect()
+}
+
+/// The key of a `/key=value` qualifier
+fn qualifier_key(q: &str) -> &str {
+    q[1..].split('=').next().unwrap_or("")
+}
+
+fn parse_qualifier(q: &str) -> (QualifierKey, Option<String>) {
+    let q = &q[1..];
+    match q.find('=') {
+        None => (QualifierKey::from(q), None),
+        Some(i) => {
+            let val = &q[i + 1..];
+            let val = if val.len() >= 2 && val.starts_with('"') && val.ends_with('"') {
+                val[1..val.len() - 1].replace("\"\"", "\"")
+            } else {
+                val.to_string()
+            };
+            (QualifierKey::from(&q[..i]), Some(val))
+        }
+    }
+}
+
+/// Split `s` into chunks no longer than `width`, breaking only after one of
+/// `breaks` (so a chunk without any stays longer), or anywhere if `breaks` is
+/// empty. A break never follows another one: a reader dropping the break
+/// from the end of each line and adding back a single one gets `s` again.
+fn wrap<'a>(mut s: &'a str, width: usize, breaks: &[char]) -> Vec<&'a str> {
+    let mut res = Vec::new();
+    while s.len() > width {
+        let split = if breaks.is_empty() {
+            let mut split = width;
+            while !s.is_char_boundary(split) {
+                split -= 1;
+            }
+            Some(split)
+        } else {
+            let mut prev_break = true;
+            let mut splits = s.char_indices().filter_map(|(i, c)| {
+                let is_break = breaks.contains(&c);
+                let split = if is_break && !prev_break {
+                    Some(i + c.len_utf8())
+                } else {
+                    None
+                };
+                prev_break = is_break;
+                split
+            });
+            // the last break that fits, or else the first one after it
+            let first = splits.next();
+            let last = splits.take_while(|&i| i <= width).last();
+            last.or(first).filter(|&i| i < s.len())
+        };
+        match split {
+            Some(split) => {
+                res.push(&s[..split]);
+                s = &s[split..];
+            }
+            None => break,
+        }
+    }
+    res.push(s);
+    res
+}
+
+fn write_wrapped<W: Write>(mut w: W, code: &str, text: &str) -> io::Result<()> {
+    for line in text.lines() {
+        for chunk in wrap(line, LINE_WIDTH - 5, &[' ']) {
+            writeln!(w, "{}   {}", code, chunk.trim_end())?;
+        }
+    }
+    writeln!(w, "XX")
+}
+
+fn write_feature<W: Write>(mut w: W, f: &Feature) -> io::Result<()> {
+    let width = LINE_WIDTH - FT_INDENT;
+    let prefix = format!("FT{:1$}", "", FT_INDENT - 2);
+    let location = location::format(&f.location);
+    for (i, chunk) in wrap(&location, width, &[',']).into_iter().enumerate() {
+        if i == 0 {
+            let kind: &str = &f.kind;
+            writeln!(w, "FT   {:<16}{}", kind, chunk)?;
+        } else {
+            writeln!(w, "{}{}", prefix, chunk)?;
+        }
+    }
+    for (key, val) in &f.qualifiers {
+        let key: &str = key;
+        let q = match val {
+            None => format!("/{}", key),
+            Some(val) if UNQUOTED.contains(&key) => format!("/{}={}", key, val),
+            Some(val) => format!("/{}=\"{}\"", key, val.replace('"', "\"\"")),
+        };
+        let breaks: &[char] = if NO_SPACE.contains(&key) { &[] } else { &[' '] };
+        for chunk in wrap(&q, width, breaks) {
+            writeln!(w, "{}{}", prefix, chunk.trim_end())?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a `Seq` in EMBL format
+pub fn write<W: Write>(mut w: W, seq: &Seq) -> io::Result<()> {
+    let name = seq.name.as_ref().map(String::as_str).unwrap_or("Untitled");
+    let version = seq
+        .version
+        .as_ref()
+        .and_then(|v| v.rsplit('.').next())
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(1);
+    let molecule_type = match seq.molecule_type.as_ref().map(String::as_str) {
+        None | Some("DNA") => "genomic DNA",
+        Some("RNA") => "genomic RNA",
+        Some(t) => t,
+    };
+    let division = if seq.division.is_empty() {
+        "UNC"
+    } else {
+        seq.division.as_str()
+    };
+    writeln!(
+        w,
+        "ID   {}; SV {}; {}; {}; STD; {}; {} BP.",
+        name,
+        version,
+        if seq.is_circular() {
+            "circular"
+        } else {
+            "linear"
+        },
+        molecule_type,
+        division,
+        seq.seq.len()
+    )?;
+    writeln!(w, "XX")?;
+    if let Some(accession) = &seq.accession {
+        writeln!(w, "AC   {};", accession)?;
+        writeln!(w, "XX")?;
+    }
+    if let Some(definition) = &seq.definition {
+        write_wrapped(&mut w, "DE", definition)?;
+    }
+    if let Some(keywords) = &seq.keywords {
+        write_wrapped(
+            &mut w,
+            "KW",
+            &format!("{}.", keywords.trim_end_matches('.')),
+        )?;
+    }
+    if let Some(source) = &seq.source {
+        writeln!(w, "OS   {}", source.source)?;
+        if let Some(organism) = &source.organism {
+            for line in organism.lines().skip(1) {
+                writeln!(w, "OC   {}", line.trim())?;
+            }
+        }
+        writeln!(w, "XX")?;
+    }
+    for comment in &seq.comments {
+        write_wrapped(&mut w, "CC", comment)?;
+    }
+    if !seq.features.is_empty() {
+        writeln!(w, "FH   Key             Location/Qualifiers")?;
+        writeln!(w, "FH")?;
+        for f in &seq.features {
+            write_feature(&mut w, f)?;
+        }
+        writeln!(w, "XX")?;
+    }
+    let count = |bases: &[u8]| {
+        seq.seq
+            .iter()
+            .filter(|c| bases.contains(&c.to_ascii_lowercase()))
+            .count()
+    };
+    let (a, c, g, t) = (count(b"a"), count(b"c"), count(b"g"), count(b"t"));
+    writeln!(
+        w,
+        "SQ   Sequence {} BP; {} A; {} C; {} G; {} T; {} other;",
+        seq.seq.len(),
+        a,
+        c,
+        g,
+        t,
+        seq.seq.len() - a - c - g - t
+    )?;
+    for (i, line) in seq.seq.chunks(60).enumerate() {
+        let blocks: Vec<_> = line
+            .chunks(10)
+            .map(|b| String::from_utf8_lossy(b))
+            .collect();
+        writeln!(
+            w,
+            "     {:<65}{:>10}",
+            blocks.join(" "),
+            i * 60 + line.len()
+        )?;
+    }
+    writeln!(w, "//")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gb_io::reader::SeqReader;
+
+    const GB: &str = r#"LOCUS       pTest                    130 bp    DNA     circular SYN 01-JAN-2019
+DEFINITION  A test plasmid.
+ACCESSION   pTest
+KEYWORDS    .
+FEATURES             Location/Qualifiers
+     source          1..130
+                     /organism="synthetic DNA construct"
+     CDS             complement(join(100..130,1..12))
+                     /gene="testA"
+                     /codon_start=1
+                     /note="a rather long note which will have to be wrapped
+                     over several lines when it is written out again"
+     misc_feature    <20..>40
+                     /pseudo
+ORIGIN
+        1 gatcctccat atacaacggt atctccacct caggtttaga tctcaacaac ggaaccattg
+       61 ccgacatgag acagttaggt atcgtcgaga gttacaagct aaaacgagca gtagtcagct
+      121 ctgcatctga
+//
+"#;
+
+    fn check(a: &Seq, b: &Seq) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.topology, b.topology);
+        assert_eq!(a.definition, b.definition);
+        assert_eq!(a.seq, b.seq);
+        assert_eq!(a.features, b.features);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let gb = SeqReader::new(GB.as_bytes()).next().unwrap().unwrap();
+        let mut embl = Vec::new();
+        write(&mut embl, &gb).unwrap();
+        let parsed = parse(&embl).unwrap();
+        assert_eq!(parsed.len(), 1);
+        check(&gb, &parsed[0]);
+
+        // and back to GenBank
+        let mut gb2 = Vec::new();
+        parsed[0].write(&mut gb2).unwrap();
+        let gb2 = SeqReader::new(&gb2[..]).next().unwrap().unwrap();
+        check(&gb, &gb2);
+    }
+
+    #[test]
+    fn test_wrapped_qualifiers() {
+        let qualifiers = vec![
+            (
+                QualifierKey::from("note"),
+                Some(format!("see https://example.org/{}", "a".repeat(100))),
+            ),
+            (
+                QualifierKey::from("note"),
+                Some(format!("{}space  out", "word ".repeat(9))),
+            ),
+            (QualifierKey::from("translation"), Some("M".repeat(150))),
+        ];
+        let mut seq = Seq {
+            seq: b"acgt".to_vec(),
+            ..Seq::empty()
+        };
+        seq.features.push(Feature {
+            kind: feature_kind!("misc_feature"),
+            location: Location::simple_range(0, 4),
+            qualifiers: qualifiers.clone(),
+        });
+        let mut embl = Vec::new();
+        write(&mut embl, &seq).unwrap();
+        let text = str::from_utf8(&embl).unwrap();
+        assert!(text.lines().all(|l| !l.ends_with(' ')));
+        assert!(text.contains("https://example.org/aaaa"));
+        let parsed = parse(&embl).unwrap();
+        assert_eq!(parsed[0].features[0].qualifiers, qualifiers);
+    }
+
+    #[test]
+    fn test_parse() {
+        let embl = br#"ID   X56734; SV 1; linear; mRNA; STD; PLN; 20 BP.
+XX
+AC   X56734; S46826;
+XX
+DE   Trifolium repens mRNA for non-cyanogenic beta-glucosidase
+XX
+FH   Key             Location/Qualifiers
+FH
+FT   CDS             join(1..5,
+FT                   11..20)
+FT                   /translation="MDFSGLSLPTNL
+FT                   SCQ"
+XX
+SQ   Sequence 20 BP; 6 A; 4 C; 4 G; 6 T; 0 other;
+     aaacaaacca aatatggatt                                                20
+//
+"#;
+        let seqs = parse(embl).unwrap();
+        assert_eq!(seqs.len(), 1);
+        let seq = &seqs[0];
+        assert_eq!(seq.name, Some("X56734".into()));
+        assert_eq!(seq.version, Some("X56734.1".into()));
+        assert_eq!(seq.accession, Some("X56734".into()));
+        assert_eq!(seq.molecule_type, Some("mRNA".into()));
+        assert_eq!(seq.seq, b"aaacaaaccaaatatggatt".to_vec());
+        assert_eq!(
+            seq.features[0].location,
+            Location::Join(vec![
+                Location::simple_range(0, 5),
+                Location::simple_range(10, 20)
+            ])
+        );
+        assert_eq!(
+            seq.features[0]
+                .qualifier_values(QualifierKey::from("translation"))
+                .next(),
+            Some("MDFSGLSLPTNLSCQ")
+        );
+    }
+}
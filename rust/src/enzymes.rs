@@ -0,0 +1,640 @@
+//! The restriction enzymes `restriction` maps, in the style of REBASE: the
+//! recognition sequence (which may contain IUPAC ambiguity codes) and where
+//! the enzyme cuts each strand. Cut positions are given on the top strand,
+//! counted from the start of the recognition sequence, so Type IIS enzymes
+//! cutting outside their site have positions past its end.
+
+pub struct Enzyme {
+    pub name: &'static str,
+    pub site: &'static [u8],
+    /// the cut in the strand the site is read on
+    pub cut: i64,
+    /// the cut in the complementary strand
+    pub cut_bottom: i64,
+}
+
+pub const ENZYMES: &[Enzyme] = &[
+    Enzyme {
+        name: "AarI",
+        site: b"CACCTGC",
+        cut: 11,
+        cut_bottom: 15,
+    },
+    Enzyme {
+        name: "AatII",
+        site: b"GACGTC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "Acc65I",
+        site: b"GGTACC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AccI",
+        site: b"GTMKAC",
+        cut: 2,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "AflII",
+        site: b"CTTAAG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AgeI",
+        site: b"ACCGGT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AhdI",
+        site: b"GACNNNNNGTC",
+        cut: 6,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AleI",
+        site: b"CACNNNNGTG",
+        cut: 5,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AluI",
+        site: b"AGCT",
+        cut: 2,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "ApaI",
+        site: b"GGGCCC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "ApaLI",
+        site: b"GTGCAC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AscI",
+        site: b"GGCGCGCC",
+        cut: 2,
+        cut_bottom: 6,
+    },
+    Enzyme {
+        name: "AvaI",
+        site: b"CYCGRG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "AvrII",
+        site: b"CCTAGG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BamHI",
+        site: b"GGATCC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BanII",
+        site: b"GRGCYC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "BbsI",
+        site: b"GAAGAC",
+        cut: 8,
+        cut_bottom: 12,
+    },
+    Enzyme {
+        name: "BclI",
+        site: b"TGATCA",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BglII",
+        site: b"AGATCT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BpiI",
+        site: b"GAAGAC",
+        cut: 8,
+        cut_bottom: 12,
+    },
+    Enzyme {
+        name: "BsaAI",
+        site: b"YACGTR",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "BsaI",
+        site: b"GGTCTC",
+        cut: 7,
+        cut_bottom: 11,
+    },
+    Enzyme {
+        name: "BsiWI",
+        site: b"CGTACG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BsmBI",
+        site: b"CGTCTC",
+        cut: 7,
+        cut_bottom: 11,
+    },
+    Enzyme {
+        name: "BsmI",
+        site: b"GAATGC",
+        cut: 7,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BspEI",
+        site: b"TCCGGA",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BspHI",
+        site: b"TCATGA",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BspQI",
+        site: b"GCTCTTC",
+        cut: 8,
+        cut_bottom: 11,
+    },
+    Enzyme {
+        name: "BsrGI",
+        site: b"TGTACA",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BstBI",
+        site: b"TTCGAA",
+        cut: 2,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "BstEII",
+        site: b"GGTNACC",
+        cut: 1,
+        cut_bottom: 6,
+    },
+    Enzyme {
+        name: "BstXI",
+        site: b"CCANNNNNNTGG",
+        cut: 8,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "Bsu36I",
+        site: b"CCTNAGG",
+        cut: 2,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "BtgZI",
+        site: b"GCGATG",
+        cut: 16,
+        cut_bottom: 20,
+    },
+    Enzyme {
+        name: "BtsI",
+        site: b"GCAGTG",
+        cut: 8,
+        cut_bottom: 6,
+    },
+    Enzyme {
+        name: "ClaI",
+        site: b"ATCGAT",
+        cut: 2,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "DpnII",
+        site: b"GATC",
+        cut: 0,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "DraI",
+        site: b"TTTAAA",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "DraIII",
+        site: b"CACNNNGTG",
+        cut: 6,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "EagI",
+        site: b"CGGCCG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "EarI",
+        site: b"CTCTTC",
+        cut: 7,
+        cut_bottom: 10,
+    },
+    Enzyme {
+        name: "EcoNI",
+        site: b"CCTNNNNNAGG",
+        cut: 5,
+        cut_bottom: 6,
+    },
+    Enzyme {
+        name: "EcoRI",
+        site: b"GAATTC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "EcoRV",
+        site: b"GATATC",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "Esp3I",
+        site: b"CGTCTC",
+        cut: 7,
+        cut_bottom: 11,
+    },
+    Enzyme {
+        name: "FokI",
+        site: b"GGATG",
+        cut: 14,
+        cut_bottom: 18,
+    },
+    Enzyme {
+        name: "FseI",
+        site: b"GGCCGGCC",
+        cut: 6,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "HaeIII",
+        site: b"GGCC",
+        cut: 2,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "HincII",
+        site: b"GTYRAC",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "HindIII",
+        site: b"AAGCTT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "HpaI",
+        site: b"GTTAAC",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "HpaII",
+        site: b"CCGG",
+        cut: 1,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "KpnI",
+        site: b"GGTACC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "MboI",
+        site: b"GATC",
+        cut: 0,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "MfeI",
+        site: b"CAATTG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "MluI",
+        site: b"ACGCGT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "MlyI",
+        site: b"GAGTC",
+        cut: 10,
+        cut_bottom: 10,
+    },
+    Enzyme {
+        name: "MseI",
+        site: b"TTAA",
+        cut: 1,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "MspI",
+        site: b"CCGG",
+        cut: 1,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "NaeI",
+        site: b"GCCGGC",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "NcoI",
+        site: b"CCATGG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "NdeI",
+        site: b"CATATG",
+        cut: 2,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "NgoMIV",
+        site: b"GCCGGC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "NheI",
+        site: b"GCTAGC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "NotI",
+        site: b"GCGGCCGC",
+        cut: 2,
+        cut_bottom: 6,
+    },
+    Enzyme {
+        name: "NruI",
+        site: b"TCGCGA",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "NsiI",
+        site: b"ATGCAT",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "PacI",
+        site: b"TTAATTAA",
+        cut: 5,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "PaeR7I",
+        site: b"CTCGAG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "PaqCI",
+        site: b"CACCTGC",
+        cut: 11,
+        cut_bottom: 15,
+    },
+    Enzyme {
+        name: "PciI",
+        site: b"ACATGT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "PflMI",
+        site: b"CCANNNNNTGG",
+        cut: 7,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "PleI",
+        site: b"GAGTC",
+        cut: 9,
+        cut_bottom: 10,
+    },
+    Enzyme {
+        name: "PmeI",
+        site: b"GTTTAAAC",
+        cut: 4,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "PshAI",
+        site: b"GACNNNNGTC",
+        cut: 5,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "PstI",
+        site: b"CTGCAG",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "PvuI",
+        site: b"CGATCG",
+        cut: 4,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "PvuII",
+        site: b"CAGCTG",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "SacI",
+        site: b"GAGCTC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "SacII",
+        site: b"CCGCGG",
+        cut: 4,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "SalI",
+        site: b"GTCGAC",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "SapI",
+        site: b"GCTCTTC",
+        cut: 8,
+        cut_bottom: 11,
+    },
+    Enzyme {
+        name: "Sau3AI",
+        site: b"GATC",
+        cut: 0,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "SbfI",
+        site: b"CCTGCAGG",
+        cut: 6,
+        cut_bottom: 2,
+    },
+    Enzyme {
+        name: "ScaI",
+        site: b"AGTACT",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "SfiI",
+        site: b"GGCCNNNNNGGCC",
+        cut: 8,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "SmaI",
+        site: b"CCCGGG",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "SnaBI",
+        site: b"TACGTA",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "SpeI",
+        site: b"ACTAGT",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "SphI",
+        site: b"GCATGC",
+        cut: 5,
+        cut_bottom: 1,
+    },
+    Enzyme {
+        name: "StuI",
+        site: b"AGGCCT",
+        cut: 3,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "StyI",
+        site: b"CCWWGG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "SwaI",
+        site: b"ATTTAAAT",
+        cut: 4,
+        cut_bottom: 4,
+    },
+    Enzyme {
+        name: "TaqI",
+        site: b"TCGA",
+        cut: 1,
+        cut_bottom: 3,
+    },
+    Enzyme {
+        name: "XbaI",
+        site: b"TCTAGA",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "XhoI",
+        site: b"CTCGAG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "XmaI",
+        site: b"CCCGGG",
+        cut: 1,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "XmnI",
+        site: b"GAANNNNTTC",
+        cut: 5,
+        cut_bottom: 5,
+    },
+    Enzyme {
+        name: "ZraI",
+        site: b"GACGTC",
+        cut: 3,
+        cut_bottom: 3,
+    },
+];
+
+/// The other enzymes recognising the same sequence, which may cut it
+/// differently (neoschizomers)
+pub fn isoschizomers(enzyme: &Enzyme) -> Vec<&'static str> {
+    ENZYMES
+        .iter()
+        .filter(|e| e.site == enzyme.site && e.name != enzyme.name)
+        .map(|e| e.name)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_enzymes() {
+        for (i, e) in ENZYMES.iter().enumerate() {
+            assert!(
+                e.site.iter().all(|c| b"ACGTRYSWKMBDHVN".contains(c)),
+                "{}",
+                e.name
+            );
+            assert!(ENZYMES[..i].iter().all(|f| f.name != e.name), "{}", e.name);
+        }
+        let kpni = ENZYMES.iter().find(|e| e.name == "KpnI").unwrap();
+        assert_eq!(isoschizomers(kpni), vec!["Acc65I"]);
+    }
+}
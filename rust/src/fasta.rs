@@ -0,0 +1,288 @@
+use gb_io::seq::*;
+use std::io::{self, Write};
+
+pub const DEFAULT_HEADER: &str = "{name}";
+
+/// Expand a header template, the supported placeholders are `{name}`,
+/// `{definition}`, `{accession}`, `{len}` and `{topology}`. Anything else,
+/// including the text substituted for a placeholder, is copied as is.
+pub fn format_header(template: &str, seq: &Seq) -> String {
+    let opt = |s: &Option<String>| s.clone().unwrap_or_default();
+    let mut res = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let value = match &rest[1..end] {
+                "name" => seq.name.clone().unwrap_or_else(|| "Untitled".into()),
+                "definition" => opt(&seq.definition),
+                "accession" => opt(&seq.accession),
+                "len" => seq.len().to_string(),
+                "topology" if seq.is_circular() => "circular".into(),
+                "topology" => "linear".into(),
+                _ => return None,
+            };
+            Some((value, end + 1))
+        });
+        match value {
+            Some((value, end)) => {
+                res.push_str(&value);
+                rest = &rest[end..];
+            }
+            None => {
+                res.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    // a header can't span multiple lines
+    res.lines().collect::<Vec<_>>().join(" ").trim().to_string()
+}
+
+/// Write `seq` as a FASTA record. The sequence is wrapped every `line_width`
+/// bases, or not at all if `line_width` is 0.
+pub fn write<W: Write>(mut w: W, seq: &Seq, line_width: usize, header: &str) -> io::Result<()> {
+    writeln!(w, ">{}", format_header(header, seq))?;
+    if line_width == 0 {
+        w.write_all(&seq.seq)?;
+        writeln!(w)?;
+    } else {
+        for line in seq.seq.chunks(line_width) {
+            w.write_all(line)?;
+            writeln!(w)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write several sequences as one multi-record FASTA file
+pub fn write_all<'a, W: Write>(
+    mut w: W,
+    seqs: impl IntoIterator<Item = &'a Seq>,
+    line_width: usize,
+    header: &str,
+) -> io::Result<()> {
+    for seq in seqs {
+        write(&mut w, seq, line_width, header)?;
+    }
+    Ok(())
+}
+
+/// What to do with a character when reading a FASTA file
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    /// skip the whole record
+    Reject,
+    /// remove the character
+    Strip,
+    /// replace the character with `N`
+    ConvertToN,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationSettings {
+    /// characters which aren't IUPAC nucleotide codes
+    pub invalid: Action,
+    /// `-` and `.`
+    pub gaps: Action,
+}
+
+impl Default for ValidationSettings {
+    fn default() -> Self {
+        ValidationSettings {
+            invalid: Action::Reject,
+            gaps: Action::Reject,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueKind {
+    Invalid,
+    Gap,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Issue {
+    /// index of the record in the file
+    pub record: usize,
+    pub id: String,
+    /// 1-based position in the record's sequence
+    pub position: usize,
+    pub character: String,
+    pub kind: IssueKind,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "record {:?}, position {}: {} {:?}",
+            self.id,
+            self.position,
+            match self.kind {
+                IssueKind::Invalid => "invalid character",
+                IssueKind::Gap => "gap",
+            },
+            self.character
+        )
+    }
+}
+
+/// Don't report more than this many issues, a binary file passed off as
+/// FASTA would otherwise produce one for almost every byte
+pub const MAX_ISSUES: usize = 1000;
+
+#[derive(Debug, Default)]
+pub struct Validated {
+    pub seqs: Vec<Seq>,
+    pub issues: Vec<Issue>,
+    /// including those which weren't reported because of `MAX_ISSUES`
+    pub total_issues: usize,
+    /// ids of the records which were skipped
+    pub rejected: Vec<String>,
+}
+
+pub(crate) fn is_iupac(c: u8) -> bool {
+    b"ACGTURYSWKMBDHVNacgturyswkmbdhvn".contains(&c)
+}
+
+/// Read a FASTA file, checking every character of each sequence. IUPAC
+/// ambiguity codes are accepted, anything else is handled according to
+/// `settings`.
+pub fn read_validated(data: &[u8], settings: &ValidationSettings) -> Result<Validated, String> {
+    let mut res = Validated::default();
+    for (idx, r) in bio::io::fasta::Reader::new(data).records().enumerate() {
+        let r = r.map_err(|e| format!("Parsing failed: {}", e))?;
+        let id = r.id().to_string();
+        let mut seq = Vec::with_capacity(r.seq().len());
+        let mut reject = false;
+        // the sequence may contain multibyte characters
+        let text = String::from_utf8_lossy(r.seq());
+        for (i, c) in text.chars().enumerate() {
+            if c.is_ascii() && is_iupac(c as u8) {
+                seq.push(c as u8);
+                continue;
+            }
+            let (kind, action) = if c == '-' || c == '.' {
+                (IssueKind::Gap, settings.gaps)
+            } else {
+                (IssueKind::Invalid, settings.invalid)
+            };
+            res.total_issues += 1;
+            if res.issues.len() < MAX_ISSUES {
+                res.issues.push(Issue {
+                    record: idx,
+                    id: id.clone(),
+                    position: i + 1,
+                    character: c.to_string(),
+                    kind,
+                });
+            }
+            match action {
+                Action::Reject => reject = true,
+                Action::Strip => {}
+                Action::ConvertToN => seq.push(b'N'),
+            }
+        }
+        if reject {
+            res.rejected.push(id);
+            continue;
+        }
+        res.seqs.push(Seq {
+            seq,
+            name: Some(id),
+            ..Seq::empty()
+        });
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let data = b">one\nACGTN\nacgtRY\n>two\nAC-GT\nA1C\n>three\nAC.G\n";
+        let res = read_validated(data, &ValidationSettings::default()).unwrap();
+        assert_eq!(res.seqs.len(), 1);
+        assert_eq!(res.rejected, vec!["two".to_string(), "three".to_string()]);
+        assert_eq!(
+            res.issues,
+            vec![
+                Issue {
+                    record: 1,
+                    id: "two".into(),
+                    position: 3,
+                    character: "-".into(),
+                    kind: IssueKind::Gap
+                },
+                Issue {
+                    record: 1,
+                    id: "two".into(),
+                    position: 7,
+                    character: "1".into(),
+                    kind: IssueKind::Invalid
+                },
+                Issue {
+                    record: 2,
+                    id: "three".into(),
+                    position: 3,
+                    character: ".".into(),
+                    kind: IssueKind::Gap
+                },
+            ]
+        );
+        let settings = ValidationSettings {
+            invalid: Action::ConvertToN,
+            gaps: Action::Strip,
+        };
+        let res = read_validated(data, &settings).unwrap();
+        assert!(res.rejected.is_empty());
+        assert_eq!(res.total_issues, 3);
+        let seqs: Vec<_> = res.seqs.iter().map(|s| &s.seq[..]).collect();
+        assert_eq!(
+            seqs,
+            vec![&b"ACGTNacgtRY"[..], &b"ACGTANC"[..], &b"ACG"[..]]
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let seq = Seq {
+            name: Some("test".into()),
+            topology: Topology::Circular,
+            seq: b"acgtacgtac".to_vec(),
+            ..Seq::empty()
+        };
+        let mut res = Vec::new();
+        write_all(&mut res, vec![&seq, &seq], 4, "{name} len={len} {topology}").unwrap();
+        assert_eq!(
+            String::from_utf8(res).unwrap(),
+            ">test len=10 circular\nacgt\nacgt\nac\n>test len=10 circular\nacgt\nacgt\nac\n"
+        );
+        let mut res = Vec::new();
+        write(&mut res, &seq, 0, DEFAULT_HEADER).unwrap();
+        assert_eq!(String::from_utf8(res).unwrap(), ">test\nacgtacgtac\n");
+    }
+
+    #[test]
+    fn test_format_header() {
+        let seq = Seq {
+            name: Some("{len}".into()),
+            definition: Some("a {name}\nover two lines".into()),
+            seq: b"acgt".to_vec(),
+            ..Seq::empty()
+        };
+        assert_eq!(
+            format_header("{name} {definition} {len} {x} {", &seq),
+            "{len} a {name} over two lines 4 {x} {"
+        );
+    }
+}
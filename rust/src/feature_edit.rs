@@ -0,0 +1,203 @@
+//! Adding, editing and deleting features. Locations are checked against the
+//! length and topology of the sequence.
+use gb_io::seq::*;
+
+/// Check that every range in `l` lies within the sequence. A feature crossing
+/// the origin of a circular sequence has to be a join of two ranges, as in
+/// `join(60..64,1..11)`.
+pub fn validate_location(seq: &Seq, l: &Location) -> Result<(), String> {
+    use Location::*;
+    let len = seq.len();
+    let circular = seq.is_circular();
+    let list = |name: &str, ls: &[Location]| -> Result<(), String> {
+        if ls.is_empty() {
+            return Err(format!("Empty {}", name));
+        }
+        ls.iter().map(|l| validate_location(seq, l)).collect()
+    };
+    match *l {
+        Range((a, _), (b, _)) => {
+            if a < 0 || b <= a || b > len {
+                let hint = if circular && a >= 0 && a < len && b > len {
+                    ", use a join to cross the origin"
+                } else {
+                    ""
+                };
+                Err(format!(
+                    "Range {}..{} is outside of the sequence (length {}){}",
+                    a + 1,
+                    b,
+                    len,
+                    hint
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        Between(a, b) => {
+            let valid = (b == a + 1 && a >= 1 && b <= len) || (circular && a == len && b == 1);
+            if valid {
+                Ok(())
+            } else {
+                Err(format!("Invalid site {}^{}", a, b))
+            }
+        }
+        Complement(ref l) => validate_location(seq, l),
+        Join(ref ls) => list("join", ls),
+        Order(ref ls) => list("order", ls),
+        Bond(ref ls) => list("bond", ls),
+        OneOf(ref ls) => list("one-of", ls),
+        // nothing to check against
+        External(..) | Gap(..) => Ok(()),
+    }
+}
+
+fn validate_name(what: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        Err(format!("Invalid {}: {:?}", what, name))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn make_qualifiers(
+    qualifiers: Vec<(String, Option<String>)>,
+) -> Result<Vec<(QualifierKey, Option<String>)>, String> {
+    qualifiers
+        .into_iter()
+        .map(|(k, v)| {
+            validate_name("qualifier key", &k)?;
+            Ok((QualifierKey::from(k.as_str()), v))
+        })
+        .collect()
+}
+
+fn check_idx(seq: &Seq, idx: usize) -> Result<(), String> {
+    if idx < seq.features.len() {
+        Ok(())
+    } else {
+        Err(format!("No such feature: {}", idx))
+    }
+}
+
+pub fn add(
+    seq: &Seq,
+    kind: &str,
+    location: Location,
+    qualifiers: Vec<(QualifierKey, Option<String>)>,
+) -> Result<Seq, String> {
+    validate_name("feature kind", kind)?;
+    validate_location(seq, &location)?;
+    let mut res = seq.clone();
+    res.features.push(Feature {
+        kind: FeatureKind::from(kind),
+        location,
+        qualifiers,
+    });
+    Ok(res)
+}
+
+pub fn set_kind(seq: &Seq, idx: usize, kind: &str) -> Result<Seq, String> {
+    check_idx(seq, idx)?;
+    validate_name("feature kind", kind)?;
+    let mut res = seq.clone();
+    res.features[idx].kind = FeatureKind::from(kind);
+    Ok(res)
+}
+
+pub fn set_location(seq: &Seq, idx: usize, location: Location) -> Result<Seq, String> {
+    check_idx(seq, idx)?;
+    validate_location(seq, &location)?;
+    let mut res = seq.clone();
+    res.features[idx].location = location;
+    Ok(res)
+}
+
+pub fn set_qualifiers(
+    seq: &Seq,
+    idx: usize,
+    qualifiers: Vec<(QualifierKey, Option<String>)>,
+) -> Result<Seq, String> {
+    check_idx(seq, idx)?;
+    let mut res = seq.clone();
+    res.features[idx].qualifiers = qualifiers;
+    Ok(res)
+}
+
+/// Delete the features with the given indices
+pub fn delete(seq: &Seq, idxes: &[usize]) -> Result<Seq, String> {
+    for &idx in idxes {
+        check_idx(seq, idx)?;
+    }
+    let mut res = seq.clone();
+    res.features = seq
+        .features
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !idxes.contains(i))
+        .map(|(_, f)| f.clone())
+        .collect();
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    fn seq(topology: Topology) -> Seq {
+        Seq {
+            topology,
+            seq: b"acgtacgtac".to_vec(),
+            ..Seq::empty()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let linear = seq(Topology::Linear);
+        let circular = seq(Topology::Circular);
+        let check = |s: &Seq, l: &str| validate_location(s, &location::parse(l).unwrap()).is_ok();
+        assert!(check(&linear, "complement(join(1..3,5..10))"));
+        assert!(check(&linear, "3^4"));
+        assert!(!check(&linear, "5..11"));
+        assert!(!check(&linear, "10^1"));
+        assert!(check(&circular, "10^1"));
+        assert!(check(&circular, "join(8..10,1..2)"));
+        assert!(!check(&circular, "11"));
+        assert!(!check(&circular, "8..12"));
+        assert!(check(&linear, "J00194.1:100..202"));
+        assert!(validate_location(&linear, &Location::Join(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_edit() {
+        let s = seq(Topology::Linear);
+        let qualifiers = make_qualifiers(vec![("label".into(), Some("test".into()))]).unwrap();
+        let s = add(
+            &s,
+            "misc_feature",
+            location::parse("1..5").unwrap(),
+            qualifiers,
+        )
+        .unwrap();
+        assert!(add(&s, "misc feature", location::parse("1..5").unwrap(), vec![]).is_err());
+        assert!(add(&s, "CDS", location::parse("1..50").unwrap(), vec![]).is_err());
+        let s = add(&s, "CDS", location::parse("2..4").unwrap(), vec![]).unwrap();
+        assert_eq!(s.features.len(), 2);
+        let s = set_location(&s, 1, location::parse("complement(3..6)").unwrap()).unwrap();
+        assert_eq!(
+            location::format(&s.features[1].location),
+            "complement(3..6)"
+        );
+        let s = set_kind(&s, 1, "gene").unwrap();
+        let qualifiers = make_qualifiers(vec![("pseudo".into(), None)]).unwrap();
+        let s = set_qualifiers(&s, 1, qualifiers).unwrap();
+        assert!(make_qualifiers(vec![("".into(), None)]).is_err());
+        let s = delete(&s, &[0]).unwrap();
+        assert_eq!(s.features.len(), 1);
+        assert_eq!(s.features[0].kind, feature_kind!("gene"));
+        assert_eq!(s.features[0].qualifiers[0].1, None);
+        assert!(delete(&s, &[1]).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! The elements `annotate::auto_annotate` looks for: common plasmid
+//! backbone parts, promoters and tags. Sequences are on the strand the
+//! element is read from; those of AmpR, lacI, ori, the trc and AmpR promoters,
+//! the lac operator and the rrnB terminators are from pTrc99a (GenBank
+//! U13872).
+
+pub struct LibraryFeature {
+    /// used as the `/label` of the features added
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub qualifiers: &'static [(&'static str, &'static str)],
+    pub seq: &'static [u8],
+}
+
+pub const LIBRARY: &[LibraryFeature] = &[
+    LibraryFeature {
+        name: "AmpR",
+        kind: "CDS",
+        qualifiers: &[
+            ("gene", "bla"),
+            ("product", "beta-lactamase"),
+            ("note", "confers resistance to ampicillin, carbenicillin, and related antibiotics"),
+        ],
+        seq: b"ATGAGTATTCAACATTTCCGTGTCGCCCTTATTCCCTTTTTTGCGGCATTTTGCCTTCCT\
+               GTTTTTGCTCACCCAGAAACGCTGGTGAAAGTAAAAGATGCTGAAGATCAGTTGGGTGCA\
+               CGAGTGGGTTACATCGAACTGGATCTCAACAGCGGTAAGATCCTTGAGAGTTTTCGCCCC\
+               GAAGAACGTTTTCCAATGATGAGCACTTTTAAAGTTCTGCTATGTGGCGCGGTATTATCC\
+               CGTGTTGACGCCGGGCAAGAGCAACTCGGTCGCCGCATACACTATTCTCAGAATGACTTG\
+               GTTGAGTACTCACCAGTCACAGAAAAGCATCTTACGGATGGCATGACAGTAAGAGAATTA\
+               TGCAGTGCTGCCATAACCATGAGTGATAACACTGCGGCCAACTTACTTCTGACAACGATC\
+               GGAGGACCGAAGGAGCTAACCGCTTTTTTGCACAACATGGGGGATCATGTAACTCGCCTT\
+               GATCGTTGGGAACCGGAGCTGAATGAAGCCATACCAAACGACGAGCGTGACACCACGATG\
+               CCTACAGCAATGGCAACAACGTTGCGCAAACTATTAACTGGCGAACTACTTACTCTAGCT\
+               TCCCGGCAACAATTAATAGACTGGATGGAGGCGGATAAAGTTGCAGGACCACTTCTGCGC\
+               TCGGCCCTTCCGGCTGGCTGGTTTATTGCTGATAAATCTGGAGCCGGTGAGCGTGGGTCT\
+               CGCGGTATCATTGCAGCACTGGGGCCAGATGGTAAGCCCTCCCGTATCGTAGTTATCTAC\
+               ACGACGGGGAGTCAGGCAACTATGGATGAACGAAATAGACAGATCGCTGAGATAGGTGCC\
+               TCACTGATTAAGCATTGGTAA",
+    },
+    LibraryFeature {
+        name: "AmpR promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("gene", "bla"),
+        ],
+        seq: b"TTCAAATATGTATCCGCTCATGAGACAAT",
+    },
+    LibraryFeature {
+        name: "ori",
+        kind: "rep_origin",
+        qualifiers: &[
+            ("note", "high-copy-number ColE1/pMB1/pBR322/pUC origin of replication"),
+        ],
+        seq: b"TTGAGATCCTTTTTTTCTGCGCGTAATCTGCTGCTTGCAAACAAAAAAACCACCGCTACC\
+               AGCGGTGGTTTGTTTGCCGGATCAAGAGCTACCAACTCTTTTTCCGAAGGTAACTGGCTT\
+               CAGCAGAGCGCAGATACCAAATACTGTCCTTCTAGTGTAGCCGTAGTTAGGCCACCACTT\
+               CAAGAACTCTGTAGCACCGCCTACATACCTCGCTCTGCTAATCCTGTTACCAGTGGCTGC\
+               TGCCAGTGGCGATAAGTCGTGTCTTACCGGGTTGGACTCAAGACGATAGTTACCGGATAA\
+               GGCGCAGCGGTCGGGCTGAACGGGGGGTTCGTGCACACAGCCCAGCTTGGAGCGAACGAC\
+               CTACACCGAACTGAGATACCTACAGCGTGAGCTATGAGAAAGCGCCACGCTTCCCGAAGG\
+               GAGAAAGGCGGACAGGTATCCGGTAAGCGGCAGGGTCGGAACAGGAGAGCGCACGAGGGA\
+               GCTTCCAGGGGGAAACGCCTGGTATCTTTATAGTCCTGTCGGGTTTCGCCACCTCTGACT\
+               TGAGCGTCGATTTTTGTGATGCTCGTCAGGGGGGCGGAGCCTATGGAAAAA",
+    },
+    LibraryFeature {
+        name: "lacI",
+        kind: "CDS",
+        qualifiers: &[
+            ("gene", "lacI"),
+            ("product", "lac repressor"),
+            ("note", "the lacIq variant has a stronger promoter"),
+        ],
+        seq: b"GTGAAACCAGTAACGTTATACGATGTCGCAGAGTATGCCGGTGTCTCTTATCAGACCGTT\
+               TCCCGCGTGGTGAACCAGGCCAGCCACGTTTCTGCGAAAACGCGGGAAAAAGTGGAAGCG\
+               GCGATGGCGGAGCTGAATTACATTCCCAACCGCGTGGCACAACAACTGGCGGGCAAACAG\
+               TCGTTGCTGATTGGCGTTGCCACCTCCAGTCTGGCCCTGCACGCGCCGTCGCAAATTGTC\
+               GCGGCGATTAAATCTCGCGCCGATCAACTGGGTGCCAGCGTGGTGGTGTCGATGGTAGAA\
+               CGAAGCGGCGTCGAAGCCTGTAAAGCGGCGGTGCACAATCTTCTCGCGCAACGCGTCAGT\
+               GGGCTGATCATTAACTATCCGCTGGATGACCAGGATGCCATTGCTGTGGAAGCTGCCTGC\
+               ACTAATGTTCCGGCGTTATTTCTTGATGTCTCTGACCAGACACCCATCAACAGTATTATT\
+               TTCTCCCATGAAGACGGTACGCGACTGGGCGTGGAGCATCTGGTCGCATTGGGTCACCAG\
+               CAAATCGCGCTGTTAGCGGGCCCATTAAGTTCTGTCTCGGCGCGTCTGCGTCTGGCTGGC\
+               TGGCATAAATATCTCACTCGCAATCAAATTCAGCCGATAGCGGAACGGGAAGGCGACTGG\
+               AGTGCCATGTCCGGTTTTCAACAAACCATGCAAATGCTGAATGAGGGCATCGTTCCCACT\
+               GCGATGCTGGTTGCCAACGATCAGATGGCGCTGGGCGCAATGCGCGCCATTACCGAGTCC\
+               GGGCTGCGCGTTGGTGCGGATATCTCGGTAGTGGGATACGACGATACCGAAGACAGCTCA\
+               TGTTATATCCCGCCGTTAACCACCATCAAACAGGATTTTCGCCTGCTGGGGCAAACCAGC\
+               GTGGACCGCTTGCTGCAACTCTCTCAGGGCCAGGCGGTGAAGGGCAATCAGCTGTTGCCC\
+               GTCTCACTGGTGAAAAGAAAAACCACCCTGGCGCCCAATACGCAAACCGCCTCTCCCCGC\
+               GCGTTGGCCGATTCATTAATGCAGCTGGCACGACAGGTTTCCCGACTGGAAAGCGGGCAG\
+               TGA",
+    },
+    LibraryFeature {
+        name: "trc promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("note", "hybrid of the trp -35 and lacUV5 -10 regions"),
+        ],
+        seq: b"TTGACAATTAATCATCCGGCTCGTATAATG",
+    },
+    LibraryFeature {
+        name: "tac promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("note", "hybrid of the trp -35 and lacUV5 -10 regions, one base closer than in the trc promoter"),
+        ],
+        seq: b"TTGACAATTAATCATCGGCTCGTATAATG",
+    },
+    LibraryFeature {
+        name: "lac promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+        ],
+        seq: b"TTTACACTTTATGCTTCCGGCTCGTATGTTG",
+    },
+    LibraryFeature {
+        name: "lac operator",
+        kind: "protein_bind",
+        qualifiers: &[
+            ("bound_moiety", "lac repressor encoded by lacI"),
+        ],
+        seq: b"TTGTGAGCGGATAACAA",
+    },
+    LibraryFeature {
+        name: "T7 promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("note", "promoter for bacteriophage T7 RNA polymerase"),
+        ],
+        seq: b"TAATACGACTCACTATAGG",
+    },
+    LibraryFeature {
+        name: "T3 promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("note", "promoter for bacteriophage T3 RNA polymerase"),
+        ],
+        seq: b"AATTAACCCTCACTAAAGG",
+    },
+    LibraryFeature {
+        name: "SP6 promoter",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "promoter"),
+            ("note", "promoter for bacteriophage SP6 RNA polymerase"),
+        ],
+        seq: b"ATTTAGGTGACACTATAG",
+    },
+    LibraryFeature {
+        name: "T7 terminator",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "terminator"),
+            ("note", "transcription terminator for bacteriophage T7 RNA polymerase"),
+        ],
+        seq: b"CTAGCATAACCCCTTGGGGCCTCTAAACGGGTCTTGAGGGGTTTTTTG",
+    },
+    LibraryFeature {
+        name: "rrnB T1 terminator",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "terminator"),
+            ("gene", "rrnB"),
+        ],
+        seq: b"ATAAAACGAAAGGCTCAGTCGAAAGACTGGGCCTTTCGTTTTAT",
+    },
+    LibraryFeature {
+        name: "rrnB T2 terminator",
+        kind: "regulatory",
+        qualifiers: &[
+            ("regulatory_class", "terminator"),
+            ("gene", "rrnB"),
+        ],
+        seq: b"AGAAGGCCATCCTGACGGATGGCCTTTT",
+    },
+    LibraryFeature {
+        name: "M13 fwd",
+        kind: "primer_bind",
+        qualifiers: &[
+            ("note", "common sequencing primer, M13 fwd (-20)"),
+        ],
+        seq: b"GTAAAACGACGGCCAGT",
+    },
+    LibraryFeature {
+        name: "M13 rev",
+        kind: "primer_bind",
+        qualifiers: &[
+            ("note", "common sequencing primer, M13 rev"),
+        ],
+        seq: b"CAGGAAACAGCTATGAC",
+    },
+    LibraryFeature {
+        name: "6xHis",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "6xHis affinity tag"),
+        ],
+        seq: b"CACCACCACCACCACCAC",
+    },
+    LibraryFeature {
+        name: "FLAG",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "FLAG epitope tag"),
+        ],
+        seq: b"GACTACAAAGACGATGACGACAAG",
+    },
+    LibraryFeature {
+        name: "HA",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "HA (human influenza hemagglutinin) epitope tag"),
+        ],
+        seq: b"TACCCATACGATGTTCCAGATTACGCT",
+    },
+    LibraryFeature {
+        name: "Myc",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "Myc (human c-Myc) epitope tag"),
+        ],
+        seq: b"GAACAAAAACTCATCTCAGAAGAGGATCTG",
+    },
+    LibraryFeature {
+        name: "V5",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "V5 epitope tag"),
+        ],
+        seq: b"GGTAAGCCTATCCCTAACCCTCTCCTCGGTCTCGATTCTACG",
+    },
+    LibraryFeature {
+        name: "Strep-tag II",
+        kind: "CDS",
+        qualifiers: &[
+            ("product", "Strep-tag II affinity tag"),
+        ],
+        seq: b"TGGAGCCACCCGCAGTTCGAAAAA",
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_library() {
+        for (i, f) in LIBRARY.iter().enumerate() {
+            assert!(f.seq.iter().all(|c| b"ACGT".contains(c)), "{}", f.name);
+            assert!(LIBRARY[..i].iter().all(|g| g.name != f.name), "{}", f.name);
+        }
+        let amp = LIBRARY.iter().find(|f| f.name == "AmpR").unwrap();
+        assert_eq!(amp.seq.len(), 861);
+    }
+}
@@ -0,0 +1,252 @@
+//! Lenient GenBank parsing: records (and features within records) which
+//! can't be parsed are skipped, and a warning is reported instead of failing
+//! the whole file.
+use gb_io::{reader::SeqReader, seq::*};
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// index of the record in the file, `None` for lines outside of any
+    /// record
+    pub record: Option<usize>,
+    /// 1-based line number
+    pub line: usize,
+    pub message: String,
+}
+
+fn parse_lines(lines: &[&str]) -> Result<Seq, String> {
+    let mut data = lines.join("\n");
+    data.push('\n');
+    match SeqReader::new(data.as_bytes()).next() {
+        Some(Ok(seq)) => Ok(seq),
+        Some(Err(e)) => Err(format!("{}", e)),
+        None => Err("No record found".into()),
+    }
+}
+
+pub fn parse(data: &[u8]) -> (Vec<Seq>, Vec<Warning>) {
+    let text = String::from_utf8_lossy(data);
+    let lines: Vec<&str> = text.lines().collect();
+    let mut warnings = Vec::new();
+    // (first line, end, terminated by `//`)
+    let mut records = Vec::new();
+    let mut start = None;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("LOCUS") {
+            if let Some(start) = start {
+                records.push((start, i, false));
+            }
+            start = Some(i);
+        } else if line.starts_with("//") {
+            match start.take() {
+                Some(start) => records.push((start, i + 1, true)),
+                None => warnings.push(Warning {
+                    record: None,
+                    line: i + 1,
+                    message: "Ignoring `//` outside of a record".into(),
+                }),
+            }
+        }
+    }
+    if let Some(start) = start {
+        records.push((start, lines.len(), false));
+    }
+    let mut res = Vec::new();
+    for (idx, &(start, end, terminated)) in records.iter().enumerate() {
+        let mut record = lines[start..end].to_vec();
+        if !terminated {
+            warnings.push(Warning {
+                record: Some(idx),
+                line: end,
+                message: "Record isn't terminated by `//`".into(),
+            });
+            record.push("//");
+        }
+        let seq = match parse_lines(&record) {
+            Ok(seq) => Some(seq),
+            Err(_) => repair(&record, idx, start, &mut warnings),
+        };
+        res.extend(seq);
+    }
+    (res, warnings)
+}
+
+/// Try to salvage a record which failed to parse, first by parsing each
+/// feature separately, then by removing invalid characters from the sequence
+fn repair(record: &[&str], idx: usize, offset: usize, warnings: &mut Vec<Warning>) -> Option<Seq> {
+    let mut warn = |line: usize, message: String| {
+        warnings.push(Warning {
+            record: Some(idx),
+            line: offset + line + 1,
+            message,
+        })
+    };
+    let features_start = record.iter().position(|l| l.starts_with("FEATURES"));
+    let features_end = features_start.map(|start| {
+        start
+            + 1
+            + record[start + 1..]
+                .iter()
+                .position(|l| !l.starts_with(' '))
+                .unwrap_or(record.len() - start - 1)
+    });
+    let header: Vec<&str> = match (features_start, features_end) {
+        (Some(start), Some(end)) => record[..start]
+            .iter()
+            .chain(record[end..].iter())
+            .cloned()
+            .collect(),
+        _ => record.to_vec(),
+    };
+    let mut cleaned = Vec::new();
+    let mut seq = match parse_lines(&header) {
+        Ok(seq) => seq,
+        Err(e) => {
+            // remove anything that isn't a base or a coordinate from the sequence
+            let origin = header.iter().position(|l| l.starts_with("ORIGIN"));
+            let origin = match origin {
+                Some(origin) => origin,
+                None => {
+                    warn(0, format!("Skipping record: {}", e));
+                    return None;
+                }
+            };
+            for l in &header[origin + 1..] {
+                if l.starts_with("//") {
+                    break;
+                }
+                cleaned.push(
+                    l.chars()
+                        .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+                        .collect::<String>(),
+                );
+            }
+            let mut repaired = header[..=origin].to_vec();
+            repaired.extend(cleaned.iter().map(String::as_str));
+            repaired.push("//");
+            match parse_lines(&repaired) {
+                Ok(seq) => {
+                    // line numbers in `header` skip the feature table
+                    let line = match (features_start, features_end) {
+                        (Some(start), Some(end)) if origin >= start => origin + end - start,
+                        _ => origin,
+                    };
+                    warn(line, "Removed invalid characters from sequence".into());
+                    seq
+                }
+                Err(e) => {
+                    warn(0, format!("Skipping record: {}", e));
+                    return None;
+                }
+            }
+        }
+    };
+    let (start, end) = match (features_start, features_end) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Some(seq),
+    };
+    // parse each feature on its own, in a record containing only the LOCUS
+    // line and the feature table
+    let is_key = |l: &str| {
+        l.starts_with("     ")
+            && l.get(5..)
+                .map(|key| !key.is_empty() && !key.starts_with(' '))
+                .unwrap_or(false)
+    };
+    let mut block_start = start + 1;
+    for i in start + 1..=end {
+        if i < end && (i == block_start || !is_key(record[i])) {
+            continue;
+        }
+        let mut mini = vec![record[0], record[start]];
+        mini.extend_from_slice(&record[block_start..i]);
+        mini.push("//");
+        match parse_lines(&mini) {
+            Ok(parsed) => seq.features.extend(parsed.features),
+            Err(e) => warn(block_start, format!("Skipping feature: {}", e)),
+        }
+        block_start = i;
+    }
+    Some(seq)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GB: &str = "LOCUS       first                     20 bp    DNA     linear   SYN 01-JAN-2019
+FEATURES             Location/Qualifiers
+     misc_feature    1..10
+                     /note=\"ok\"
+     misc_feature    join(1..5,
+                     /note=\"broken\"
+     misc_feature    11..20
+ORIGIN
+        1 acgtacgtac gtacgtacgt
+//
+LOCUS       second                    10 bp    DNA     linear   SYN 01-JAN-2019
+FEATURES             Location/Qualifiers
+     misc_feature    1..10
+ORIGIN
+        1 acgtacgtac
+//
+";
+
+    #[test]
+    fn test_lenient() {
+        let (seqs, warnings) = parse(GB.as_bytes());
+        assert_eq!(seqs.len(), 2);
+        assert_eq!(seqs[0].seq, b"acgtacgtacgtacgtacgt".to_vec());
+        assert_eq!(seqs[0].features.len(), 2);
+        assert_eq!(seqs[1].features.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].record, Some(0));
+        assert_eq!(warnings[0].line, 5);
+    }
+
+    #[test]
+    fn test_invalid_characters() {
+        let gb = "LOCUS       first                     20 bp    DNA     linear   SYN 01-JAN-2019
+ORIGIN
+        1 acgta!cgta cgtacgt#cg
+//
+";
+        let (seqs, warnings) = parse(gb.as_bytes());
+        assert_eq!(seqs.len(), 1);
+        assert_eq!(seqs[0].seq, b"acgtacgtacgtacgtacgt".to_vec());
+        assert_eq!(
+            warnings,
+            vec![Warning {
+                record: Some(0),
+                line: 2,
+                message: "Removed invalid characters from sequence".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unterminated() {
+        let gb = "//
+LOCUS       second                    10 bp    DNA     linear   SYN 01-JAN-2019
+ORIGIN
+        1 acgtacgtac
+";
+        let (seqs, warnings) = parse(gb.as_bytes());
+        assert_eq!(seqs.len(), 1);
+        assert_eq!(seqs[0].seq, b"acgtacgtac".to_vec());
+        assert_eq!(
+            warnings,
+            vec![
+                Warning {
+                    record: None,
+                    line: 1,
+                    message: "Ignoring `//` outside of a record".into(),
+                },
+                Warning {
+                    record: Some(0),
+                    line: 4,
+                    message: "Record isn't terminated by `//`".into(),
+                }
+            ]
+        );
+    }
+}
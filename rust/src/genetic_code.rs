@@ -0,0 +1,280 @@
+//! The NCBI genetic codes (translation tables), as given in
+//! <https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi>. Codons are
+//! indexed with T, C, A, G = 0..3, first base most significant. Stops which
+//! only end translation in some contexts (tables 27, 28 and 31) are
+//! translated as their amino acid.
+
+pub struct GeneticCode {
+    pub id: u8,
+    pub name: &'static str,
+    /// the amino acid of each codon, `*` for stops
+    aas: &'static [u8; 64],
+    /// `M` for codons which can start translation
+    starts: &'static [u8; 64],
+}
+
+pub const CODES: &[GeneticCode] = &[
+    GeneticCode {
+        id: 1,
+        name: "Standard",
+        aas: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 2,
+        name: "Vertebrate Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        starts: b"----------**--------------------MMMM----------**---M------------",
+    },
+    GeneticCode {
+        id: 3,
+        name: "Yeast Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**----------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 4,
+        name: "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--MM------**-------M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 5,
+        name: "Invertebrate Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        starts: b"---M------**--------------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 6,
+        name: "Ciliate, Dasycladacean and Hexamita Nuclear",
+        aas: b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 9,
+        name: "Echinoderm and Flatworm Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"----------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 10,
+        name: "Euplotid Nuclear",
+        aas: b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 11,
+        name: "Bacterial, Archaeal and Plant Plastid",
+        aas: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 12,
+        name: "Alternative Yeast Nuclear",
+        aas: b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 13,
+        name: "Ascidian Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        starts: b"---M------**----------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 14,
+        name: "Alternative Flatworm Mitochondrial",
+        aas: b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------*-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 15,
+        name: "Blepharisma Macronuclear",
+        aas: b"FFLLSSSSYY*QCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 16,
+        name: "Chlorophycean Mitochondrial",
+        aas: b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 21,
+        name: "Trematode Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"----------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 22,
+        name: "Scenedesmus obliquus Mitochondrial",
+        aas: b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"------*---*---*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 23,
+        name: "Thraustochytrium Mitochondrial",
+        aas: b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--*-------**--*-----------------M--M---------------M------------",
+    },
+    GeneticCode {
+        id: 24,
+        name: "Rhabdopleuridae Mitochondrial",
+        aas: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: b"---M------**-------M---------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 25,
+        name: "Candidate Division SR1 and Gracilibacteria",
+        aas: b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**-----------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 26,
+        name: "Pachysolen tannophilus Nuclear",
+        aas: b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**--*----M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 27,
+        name: "Karyorelict Nuclear",
+        aas: b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 28,
+        name: "Condylostoma Nuclear",
+        aas: b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**--*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 29,
+        name: "Mesodinium Nuclear",
+        aas: b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 30,
+        name: "Peritrich Nuclear",
+        aas: b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------*--------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 31,
+        name: "Blastocrithidia Nuclear",
+        aas: b"FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**-----------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 33,
+        name: "Cephalodiscidae Mitochondrial UAA-Tyr",
+        aas: b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: b"---M-------*-------M---------------M---------------M------------",
+    },
+];
+
+pub const DEFAULT_TABLE: u8 = 1;
+
+pub fn get(id: u8) -> Result<&'static GeneticCode, String> {
+    CODES
+        .iter()
+        .find(|c| c.id == id)
+        .ok_or_else(|| format!("Unknown translation table: {}", id))
+}
+
+/// The indices of the bases (T, C, A, G) an IUPAC code can stand for, as
+/// bits
+pub(crate) fn base_bits(c: u8) -> u8 {
+    match c.to_ascii_uppercase() {
+        b'T' | b'U' => 0b0001,
+        b'C' => 0b0010,
+        b'A' => 0b0100,
+        b'G' => 0b1000,
+        b'Y' => 0b0011,
+        b'R' => 0b1100,
+        b'W' => 0b0101,
+        b'S' => 0b1010,
+        b'K' => 0b1001,
+        b'M' => 0b0110,
+        b'B' => 0b1011,
+        b'D' => 0b1101,
+        b'H' => 0b0111,
+        b'V' => 0b1110,
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
+/// Every codon index `codon` can stand for
+fn codon_indices(codon: &[u8]) -> Vec<usize> {
+    let mut res = vec![0];
+    for &c in codon {
+        let bits = base_bits(c);
+        res = res
+            .iter()
+            .flat_map(|&i| {
+                (0..4)
+                    .filter(move |b| bits & (1 << b) != 0)
+                    .map(move |b| i * 4 + b)
+            })
+            .collect();
+    }
+    res
+}
+
+impl GeneticCode {
+    /// `X` if the codon is ambiguous, unless all its possible codons give
+    /// the same amino acid
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        let idxes = codon_indices(codon);
+        match idxes.first() {
+            Some(&first) if idxes.iter().all(|&i| self.aas[i] == self.aas[first]) => {
+                self.aas[first]
+            }
+            _ => b'X',
+        }
+    }
+
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        let idxes = codon_indices(codon);
+        !idxes.is_empty() && idxes.iter().all(|&i| self.starts[i] == b'M')
+    }
+
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        self.translate_codon(codon) == b'*'
+    }
+
+    /// Translate whole codons of `seq`. If `start` is set the first codon is
+    /// translated as a start codon, i.e. as `M` if it can be one.
+    pub fn translate(&self, seq: &[u8], start: bool) -> Vec<u8> {
+        seq.chunks_exact(3)
+            .enumerate()
+            .map(|(i, codon)| {
+                if i == 0 && start && self.is_start(codon) {
+                    b'M'
+                } else {
+                    self.translate_codon(codon)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        let standard = get(1).unwrap();
+        assert_eq!(standard.translate(b"ATGGCNtgaTAR", false), b"MA**".to_vec());
+        assert_eq!(standard.translate(b"TTGAAYRAYGC", true), b"MNX".to_vec());
+        assert_eq!(standard.translate(b"TTGAAY", false), b"LN".to_vec());
+        assert!(!standard.is_start(b"GTG"));
+        assert!(get(11).unwrap().is_start(b"GTG"));
+        let mito = get(2).unwrap();
+        assert_eq!(mito.translate(b"TGAAGAATA", false), b"W*M".to_vec());
+        assert_eq!(get(31).unwrap().translate(b"TAA", false), b"E".to_vec());
+        assert!(get(7).is_err());
+        assert_eq!(CODES.len(), 26);
+    }
+}
@@ -0,0 +1,450 @@
+use gb_io::seq::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str;
+
+use crate::location::for_each_range;
+
+const SOURCE: &str = "clonifier";
+
+/// GFF3 reserved attributes, and the qualifiers they map to
+const ATTRIBUTES: &[(&str, &str)] = &[("Note", "note"), ("Dbxref", "db_xref")];
+
+fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\t' | '\n' | '\r' | '%' | ';' | '=' | '&' | ',' => {
+                res.push_str(&format!("%{:02X}", c as u32))
+            }
+            c if c.is_control() => res.push_str(&format!("%{:02X}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Seqids may only contain alphanumerics and a few punctuation characters,
+/// anything else is escaped
+fn escape_seqid(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || ".:^*$@!+_?-|".contains(c) {
+            res.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                res.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    res
+}
+
+fn unescape(s: &str) -> String {
+    let s = s.as_bytes();
+    let mut res = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        let decoded = if s[i] == b'%' && i + 2 < s.len() {
+            str::from_utf8(&s[i + 1..i + 3])
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        } else {
+            None
+        };
+        match decoded {
+            Some(c) => {
+                res.push(c);
+                i += 3;
+            }
+            None => {
+                res.push(s[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+/// The ranges making up a location in 5' -> 3' order, merging ranges that
+/// span the origin of circular sequences, so `end` may be greater than the
+/// sequence length.
+fn segments(seq: &Seq, l: &Location) -> Vec<(i64, i64, bool)> {
+    let mut res: Vec<(i64, i64, bool)> = Vec::new();
+    let mut add_range = |start, end, fwd| {
+        if let Some(last) = res.last_mut() {
+            if seq.is_circular() && start == 0 && last.1 == seq.len() && last.2 == fwd {
+                last.1 += end;
+                return;
+            }
+        }
+        res.push((start, end, fwd));
+    };
+    for_each_range(l, &mut add_range, true);
+    if res.iter().all(|&(_, _, fwd)| !fwd) {
+        res.reverse();
+    }
+    res
+}
+
+/// Write the features of `seq` in GFF3 format
+pub fn write<W: Write>(mut w: W, seq: &Seq) -> io::Result<()> {
+    let seqid = escape_seqid(seq.name.as_ref().map(String::as_str).unwrap_or("Untitled"));
+    writeln!(w, "##gff-version 3")?;
+    writeln!(w, "##sequence-region {} 1 {}", seqid, seq.len())?;
+    if seq.is_circular() {
+        writeln!(
+            w,
+            "{}\t{}\tregion\t1\t{}\t.\t+\t.\tID={};Is_circular=true",
+            seqid,
+            SOURCE,
+            seq.len(),
+            seqid
+        )?;
+    }
+    for (idx, f) in seq.features.iter().enumerate() {
+        let mut attributes = vec![format!("ID=feature{}", idx + 1)];
+        let name = f
+            .qualifier_values(qualifier_key!("label"))
+            .chain(f.qualifier_values(qualifier_key!("gene")))
+            .next();
+        if let Some(name) = name {
+            attributes.push(format!("Name={}", escape(name)));
+        }
+        // group multiple values for the same key, keeping the original order
+        let mut grouped: Vec<(&str, Vec<String>)> = Vec::new();
+        for (key, val) in &f.qualifiers {
+            let key: &str = key;
+            let key = ATTRIBUTES
+                .iter()
+                .find(|&&(_, q)| q == key)
+                .map(|&(a, _)| a)
+                .unwrap_or(key);
+            let val = val.as_ref().map(|v| escape(v)).unwrap_or_default();
+            match grouped.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, vals)) => vals.push(val),
+                None => grouped.push((key, vec![val])),
+            }
+        }
+        attributes.extend(
+            grouped
+                .into_iter()
+                .map(|(k, vals)| format!("{}={}", escape(k), vals.join(","))),
+        );
+        let attributes = attributes.join(";");
+        let mut phase = f
+            .qualifier_values(QualifierKey::from("codon_start"))
+            .next()
+            .and_then(|c| c.parse::<i64>().ok())
+            .map(|c| (c - 1).max(0))
+            .unwrap_or(0);
+        let kind: &str = &f.kind;
+        for (start, end, fwd) in segments(seq, &f.location) {
+            writeln!(
+                w,
+                "{}\t{}\t{}\t{}\t{}\t.\t{}\t{}\t{}",
+                seqid,
+                SOURCE,
+                escape(kind),
+                start + 1,
+                end,
+                if fwd { '+' } else { '-' },
+                if kind == "CDS" {
+                    phase.to_string()
+                } else {
+                    ".".into()
+                },
+                attributes
+            )?;
+            // the number of bases to skip at the start of the next segment
+            phase = (3 - (end - start - phase).rem_euclid(3)) % 3;
+        }
+    }
+    Ok(())
+}
+
+struct Row {
+    start: i64,
+    end: i64,
+    fwd: bool,
+    phase: Option<i64>,
+}
+
+/// Parse the features in a GFF3 file belonging to `seq`, matching by seqid.
+/// If `seq` has no name, the file must only contain a single seqid.
+pub fn parse_features(data: &[u8], seq: &Seq) -> Result<Vec<Feature>, String> {
+    let text = str::from_utf8(data).map_err(|e| format!("Invalid UTF-8: {}", e))?;
+    let name = seq.name.as_ref().map(String::as_str);
+    let len = seq.len();
+    // (ID or None, kind, attributes, rows)
+    let mut features: Vec<(Option<String>, String, Vec<(String, String)>, Vec<Row>)> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut found_seqids = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with("##FASTA") {
+            break;
+        }
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("Line {}: {}", i + 1, msg);
+        let cols: Vec<_> = line.split('\t').collect();
+        if cols.len() != 9 {
+            return Err(err("Expected 9 tab-separated columns"));
+        }
+        let seqid = unescape(cols[0]);
+        if !found_seqids.contains(&seqid) {
+            found_seqids.push(seqid.clone());
+        }
+        match name {
+            Some(name) if name != seqid => continue,
+            None if found_seqids.len() > 1 => {
+                return Err(err(&format!(
+                    "The sequence has no name to match, but the file contains {}",
+                    found_seqids.join(", ")
+                )));
+            }
+            _ => {}
+        }
+        let start: i64 = cols[3].parse().map_err(|_| err("Invalid start"))?;
+        let end: i64 = cols[4].parse().map_err(|_| err("Invalid end"))?;
+        // on circular sequences the end may lie past the origin, but the
+        // feature can't be longer than the sequence
+        let max_end = if seq.is_circular() {
+            start - 1 + len
+        } else {
+            len
+        };
+        if start < 1 || start > len || end < start || end > max_end {
+            return Err(err(&format!(
+                "Coordinates {}..{} out of range (sequence length {})",
+                start, end, len
+            )));
+        }
+        let fwd = match cols[6] {
+            "-" => false,
+            _ => true,
+        };
+        let phase = cols[7].parse().ok();
+        let attributes: Vec<(String, String)> = cols[8]
+            .split(';')
+            .filter(|a| !a.trim().is_empty())
+            .map(|a| {
+                let mut kv = a.splitn(2, '=');
+                let key = unescape(kv.next().unwrap_or_default().trim());
+                (key, kv.next().unwrap_or_default().to_string())
+            })
+            .collect();
+        let kind = unescape(cols[2]);
+        let is_circular_region = attributes
+            .iter()
+            .any(|(k, v)| k == "Is_circular" && v == "true");
+        if kind == "region" && is_circular_region {
+            continue;
+        }
+        let row = Row {
+            start: start - 1,
+            end,
+            fwd,
+            phase,
+        };
+        let id = attributes
+            .iter()
+            .find(|(k, _)| k == "ID")
+            .map(|(_, v)| unescape(v));
+        match id.as_ref().and_then(|id| by_id.get(id)) {
+            // multi-line feature, e.g. a spliced CDS
+            Some(&idx) => features[idx].3.push(row),
+            None => {
+                if let Some(id) = &id {
+                    by_id.insert(id.clone(), features.len());
+                }
+                features.push((id, kind, attributes, vec![row]));
+            }
+        }
+    }
+    if features.is_empty() {
+        return Err(format!(
+            "No features for sequence '{}' found, the file contains: {}",
+            name.unwrap_or_default(),
+            found_seqids.join(", ")
+        ));
+    }
+    Ok(features
+        .into_iter()
+        .map(|(_, kind, attributes, rows)| to_feature(kind, attributes, rows, len))
+        .collect())
+}
+
+fn to_feature(
+    kind: String,
+    attributes: Vec<(String, String)>,
+    mut rows: Vec<Row>,
+    len: i64,
+) -> Feature {
+    let all_rev = rows.iter().all(|r| !r.fwd);
+    // the rows are kept in the order of the file, as joins needn't be in
+    // ascending order. Minus strand features may be listed 5' -> 3' (as
+    // `write` does) or in ascending order, the location is built ascending.
+    if all_rev && rows.len() > 1 && rows[0].start > rows[rows.len() - 1].start {
+        rows.reverse();
+    }
+    // the phase of the 5' segment
+    let first = if all_rev { rows.last() } else { rows.first() };
+    let phase = first.and_then(|r| r.phase).unwrap_or(0);
+    let mut ranges = Vec::new();
+    for r in &rows {
+        let parts = if r.end > len {
+            // spans the origin
+            vec![
+                Location::simple_range(r.start, len),
+                Location::simple_range(0, r.end - len),
+            ]
+        } else {
+            vec![Location::simple_range(r.start, r.end)]
+        };
+        for p in parts {
+            if r.fwd || all_rev {
+                ranges.push(p);
+            } else {
+                ranges.push(Location::Complement(Box::new(p)));
+            }
+        }
+    }
+    let mut location = if ranges.len() == 1 {
+        ranges.pop().unwrap()
+    } else {
+        Location::Join(ranges)
+    };
+    if all_rev {
+        location = Location::Complement(Box::new(location));
+    }
+    let has_label = attributes.iter().any(|(k, _)| k == "label" || k == "gene");
+    let mut qualifiers = Vec::new();
+    for (key, vals) in attributes {
+        let key = match key.as_str() {
+            "ID" | "Parent" | "Is_circular" => continue,
+            "Name" if has_label => continue,
+            "Name" => "label",
+            k => ATTRIBUTES
+                .iter()
+                .find(|&&(a, _)| a == k)
+                .map(|&(_, q)| q)
+                .unwrap_or(k),
+        };
+        let key = QualifierKey::from(key);
+        for val in vals.split(',') {
+            let val = unescape(val);
+            qualifiers.push((key.clone(), if val.is_empty() { None } else { Some(val) }));
+        }
+    }
+    if kind == "CDS" && phase != 0 && !qualifiers.iter().any(|(k, _)| &**k == "codon_start") {
+        qualifiers.push((
+            QualifierKey::from("codon_start"),
+            Some((phase + 1).to_string()),
+        ));
+    }
+    Feature {
+        kind: FeatureKind::from(kind.as_str()),
+        location,
+        qualifiers,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    #[test]
+    fn test_round_trip() {
+        let locations = &[
+            "1..10",
+            "complement(join(1..10,21..30))",
+            "join(91..100,1..10)",
+            "complement(join(91..100,1..10))",
+            "join(1..10,complement(21..30))",
+            "join(21..30,1..10)",
+        ];
+        let seq = Seq {
+            name: Some("test seq".into()),
+            topology: Topology::Circular,
+            seq: vec![b'a'; 100],
+            features: locations
+                .iter()
+                .map(|l| Feature {
+                    kind: feature_kind!("CDS"),
+                    location: location::parse(l).unwrap(),
+                    qualifiers: vec![
+                        (qualifier_key!("gene"), Some("a;b=c,d".into())),
+                        (qualifier_key!("note"), Some("first".into())),
+                        (qualifier_key!("note"), Some("second".into())),
+                        (QualifierKey::from("pseudo"), None),
+                    ],
+                })
+                .collect(),
+            ..Seq::empty()
+        };
+        let mut gff = Vec::new();
+        write(&mut gff, &seq).unwrap();
+        assert!(str::from_utf8(&gff).unwrap().contains("\ntest%20seq\t"));
+        let features = parse_features(&gff, &seq).unwrap();
+        assert_eq!(features, seq.features);
+    }
+
+    #[test]
+    fn test_parse() {
+        let gff = b"##gff-version 3
+ctg123\t.\tCDS\t1201\t1500\t.\t-\t0\tID=cds1;Name=edenprotein.1
+ctg123\t.\tCDS\t3000\t3902\t.\t-\t2\tID=cds1;Name=edenprotein.1
+other\t.\tgene\t1\t10\t.\t+\t.\tID=gene1
+";
+        let seq = Seq {
+            name: Some("ctg123".into()),
+            seq: vec![b'a'; 4000],
+            ..Seq::empty()
+        };
+        let features = parse_features(gff, &seq).unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(
+            location::format(&features[0].location),
+            "complement(join(1201..1500,3000..3902))"
+        );
+        assert_eq!(
+            features[0].qualifiers,
+            vec![
+                (qualifier_key!("label"), Some("edenprotein.1".into())),
+                (QualifierKey::from("codon_start"), Some("3".into())),
+            ]
+        );
+        let seq = Seq {
+            name: Some("missing".into()),
+            ..seq
+        };
+        assert!(parse_features(gff, &seq).is_err());
+        // several seqids, none of which can be matched
+        let seq = Seq { name: None, ..seq };
+        assert!(parse_features(gff, &seq).is_err());
+    }
+
+    #[test]
+    fn test_parse_circular() {
+        let seq = Seq {
+            topology: Topology::Circular,
+            seq: vec![b'a'; 10],
+            ..Seq::empty()
+        };
+        let gff = b"c\t.\tgene\t8\t12\t.\t+\t.\tID=gene1\n";
+        let features = parse_features(gff, &seq).unwrap();
+        assert_eq!(location::format(&features[0].location), "join(8..10,1..2)");
+        for row in &["c\t.\tgene\t11\t12", "c\t.\tgene\t3\t13"] {
+            let gff = format!("{}\t.\t+\t.\tID=gene1\n", row);
+            assert_eq!(
+                parse_features(gff.as_bytes(), &seq).map_err(|e| e.starts_with("Line 1: ")),
+                Err(true)
+            );
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! Edit history of a sequence. Every state is kept (sequences are shared
+//! through `Rc` so this is cheap), as a tree: making an edit after undoing
+//! starts a new branch instead of discarding the undone states.
+//!
+//! The serialized layout is `HISTORY_MAGIC`, a bincode encoded
+//! `HistoryVersion` tag and a bincode encoded `HistoryFile`. States sharing a
+//! sequence store it once, and so do sequences sharing their bases, e.g.
+//! after editing only the features, each in the binary sequence format.
+use crate::bin_format;
+use crate::JsSeq;
+use bincode::{deserialize_from, serialize_into};
+use gb_io::seq::Seq;
+use js_sys::Error;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+pub const HISTORY_MAGIC: &[u8] = b"CLNHIST\x00";
+
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct HistoryVersion(pub u32);
+
+pub const HISTORY_FORMAT_VERSION: HistoryVersion = HistoryVersion(1);
+
+/// An operation, `params` is JSON so the frontend can record anything
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Operation {
+    pub name: String,
+    pub params: String,
+}
+
+struct State {
+    parent: Option<usize>,
+    op: Operation,
+    seq: Rc<Seq>,
+    /// the child `redo` goes to, the one created or visited most recently
+    redo: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateFile {
+    parent: Option<usize>,
+    op: Operation,
+    /// index into `HistoryFile::seqs`
+    seq: usize,
+    redo: Option<usize>,
+}
+
+/// A sequence without its bases, which are `HistoryFile::bases[bases]`
+#[derive(Serialize, Deserialize)]
+struct SeqFile {
+    bases: usize,
+    meta: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HistoryFile {
+    bases: Vec<Vec<u8>>,
+    seqs: Vec<SeqFile>,
+    states: Vec<StateFile>,
+    current: usize,
+}
+
+/// A state as listed by `SeqHistory::get_entries`
+#[derive(Serialize)]
+struct Entry<'a> {
+    id: usize,
+    parent: Option<usize>,
+    name: &'a str,
+    params: serde_json::Value,
+    current: bool,
+}
+
+#[wasm_bindgen]
+pub struct SeqHistory {
+    states: Vec<State>,
+    current: usize,
+}
+
+impl SeqHistory {
+    pub fn from_seq(seq: Rc<Seq>) -> SeqHistory {
+        SeqHistory {
+            states: vec![State {
+                parent: None,
+                op: Operation {
+                    name: "open".into(),
+                    params: "null".into(),
+                },
+                seq,
+                redo: None,
+            }],
+            current: 0,
+        }
+    }
+
+    pub fn current_seq(&self) -> &Rc<Seq> {
+        &self.states[self.current].seq
+    }
+
+    pub fn record(&mut self, op: Operation, seq: Rc<Seq>) {
+        let id = self.states.len();
+        self.states.push(State {
+            parent: Some(self.current),
+            op,
+            seq,
+            redo: None,
+        });
+        self.states[self.current].redo = Some(id);
+        self.current = id;
+    }
+
+    pub fn undo_impl(&mut self) -> bool {
+        match self.states[self.current].parent {
+            Some(parent) => {
+                self.states[parent].redo = Some(self.current);
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo_impl(&mut self) -> bool {
+        match self.states[self.current].redo {
+            Some(child) => {
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Jump to any state, e.g. on another branch. Redo from each of its
+    /// ancestors will now lead back to it.
+    pub fn checkout_impl(&mut self, id: usize) -> Result<(), String> {
+        if id >= self.states.len() {
+            return Err(format!("No such state: {}", id));
+        }
+        let mut child = id;
+        while let Some(parent) = self.states[child].parent {
+            self.states[parent].redo = Some(child);
+            child = parent;
+        }
+        self.current = id;
+        Ok(())
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut seqs: Vec<&Rc<Seq>> = Vec::new();
+        let mut bases: Vec<&[u8]> = Vec::new();
+        let mut file = HistoryFile {
+            bases: Vec::new(),
+            seqs: Vec::new(),
+            states: Vec::with_capacity(self.states.len()),
+            current: self.current,
+        };
+        for s in &self.states {
+            let seq = match seqs.iter().position(|&seq| Rc::ptr_eq(seq, &s.seq)) {
+                Some(i) => i,
+                None => {
+                    let b = match bases.iter().position(|&b| b == &s.seq.seq[..]) {
+                        Some(i) => i,
+                        None => {
+                            bases.push(&s.seq.seq);
+                            file.bases.push(bin_format::encode_bases(&s.seq.seq)?);
+                            bases.len() - 1
+                        }
+                    };
+                    seqs.push(&s.seq);
+                    file.seqs.push(SeqFile {
+                        bases: b,
+                        meta: bin_format::encode_without_bases(&s.seq)?,
+                    });
+                    seqs.len() - 1
+                }
+            };
+            file.states.push(StateFile {
+                parent: s.parent,
+                op: s.op.clone(),
+                seq,
+                redo: s.redo,
+            });
+        }
+        let mut res = HISTORY_MAGIC.to_vec();
+        let mut buf = &mut res;
+        serialize_into(&mut buf, &HISTORY_FORMAT_VERSION).expect("Writing header failed");
+        serialize_into(&mut buf, &file).map_err(|e| format!("Couldn't encode: {}", e))?;
+        Ok(res)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<SeqHistory, String> {
+        if !data.starts_with(HISTORY_MAGIC) {
+            return Err("Not an edit history".into());
+        }
+        let mut data = &data[HISTORY_MAGIC.len()..];
+        let ver: HistoryVersion =
+            deserialize_from(&mut data).map_err(|e| format!("Decoding header failed: {}", e))?;
+        let file: HistoryFile = match ver.0 {
+            1 => deserialize_from(&mut data).map_err(|e| format!("Decoding failed: {}", e))?,
+            v => return Err(format!("Can't decode version {} of edit history", v)),
+        };
+        let n = file.states.len();
+        let valid = |idx: Option<usize>| idx.map(|i| i < n).unwrap_or(true);
+        // `redo` must lead to a child of its state
+        let valid_redo =
+            |i: usize, s: &StateFile| s.redo.map_or(true, |r| file.states[r].parent == Some(i));
+        if file.current >= n
+            || file.seqs.iter().any(|s| s.bases >= file.bases.len())
+            || file.states.iter().enumerate().any(|(i, s)| {
+                !valid(s.parent)
+                    || !valid(s.redo)
+                    || s.parent.map_or(false, |p| p >= i)
+                    || s.seq >= file.seqs.len()
+                    || !valid_redo(i, s)
+            })
+        {
+            return Err("Decoding failed: invalid history".into());
+        }
+        let bases = file
+            .bases
+            .iter()
+            .map(|b| bin_format::decode(b).map(|s| s.seq))
+            .collect::<Result<Vec<_>, String>>()?;
+        let seqs = file
+            .seqs
+            .iter()
+            .map(|s| {
+                let seq = bin_format::decode(&s.meta)?;
+                Ok(Rc::new(Seq {
+                    seq: bases[s.bases].clone(),
+                    ..seq
+                }))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let states = file
+            .states
+            .into_iter()
+            .map(|s| State {
+                parent: s.parent,
+                op: s.op,
+                seq: seqs[s.seq].clone(),
+                redo: s.redo,
+            })
+            .collect();
+        Ok(SeqHistory {
+            states,
+            current: file.current,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl SeqHistory {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seq: &JsSeq) -> SeqHistory {
+        SeqHistory::from_seq(seq.0.clone())
+    }
+    /// Record the result of an operation, `params` can be any JSON value
+    pub fn push(&mut self, name: String, params: &JsValue, seq: &JsSeq) -> Result<(), JsValue> {
+        let params: serde_json::Value = if params.is_undefined() {
+            serde_json::Value::Null
+        } else {
+            params
+                .into_serde()
+                .map_err(|e| Error::new(&format!("Invalid parameters: {}", e)))?
+        };
+        let op = Operation {
+            name,
+            params: params.to_string(),
+        };
+        self.record(op, seq.0.clone());
+        Ok(())
+    }
+    pub fn current(&self) -> JsSeq {
+        JsSeq(self.current_seq().clone())
+    }
+    pub fn can_undo(&self) -> bool {
+        self.states[self.current].parent.is_some()
+    }
+    pub fn can_redo(&self) -> bool {
+        self.states[self.current].redo.is_some()
+    }
+    /// Returns the sequence after undoing, fails if there is nothing to undo
+    pub fn undo(&mut self) -> Result<JsSeq, JsValue> {
+        if !self.undo_impl() {
+            return Err(Error::new("Nothing to undo").into());
+        }
+        Ok(self.current())
+    }
+    pub fn redo(&mut self) -> Result<JsSeq, JsValue> {
+        if !self.redo_impl() {
+            return Err(Error::new("Nothing to redo").into());
+        }
+        Ok(self.current())
+    }
+    pub fn checkout(&mut self, id: usize) -> Result<JsSeq, JsValue> {
+        self.checkout_impl(id).map_err(|e| Error::new(&e))?;
+        Ok(self.current())
+    }
+    /// All states, in the order they were created
+    pub fn get_entries(&self) -> JsValue {
+        let entries: Vec<_> = self
+            .states
+            .iter()
+            .enumerate()
+            .map(|(id, s)| Entry {
+                id,
+                parent: s.parent,
+                name: &s.op.name,
+                params: serde_json::from_str(&s.op.params).unwrap_or_default(),
+                current: id == self.current,
+            })
+            .collect();
+        JsValue::from_serde(&entries).unwrap()
+    }
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        self.encode().map_err(|e| Error::new(&e).into())
+    }
+}
+
+#[wasm_bindgen]
+pub fn parse_history(data: &[u8]) -> Result<SeqHistory, JsValue> {
+    SeqHistory::decode(data).map_err(|e| Error::new(&e).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gb_io::seq::{Feature, Location};
+
+    fn seq(bases: &[u8]) -> Rc<Seq> {
+        Rc::new(Seq {
+            seq: bases.to_vec(),
+            ..Seq::empty()
+        })
+    }
+
+    fn op(name: &str) -> Operation {
+        Operation {
+            name: name.into(),
+            params: "{}".into(),
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut h = SeqHistory::from_seq(seq(b"a"));
+        assert!(!h.undo_impl());
+        h.record(op("insert"), seq(b"ac"));
+        h.record(op("insert"), seq(b"acg"));
+        assert!(h.undo_impl());
+        assert!(h.undo_impl());
+        assert_eq!(h.current_seq().seq, b"a");
+        assert!(h.redo_impl());
+        assert_eq!(h.current_seq().seq, b"ac");
+        // start a new branch
+        h.record(op("delete"), seq(b"c"));
+        assert!(!h.redo_impl());
+        assert!(h.undo_impl());
+        assert!(h.redo_impl());
+        assert_eq!(h.current_seq().seq, b"c");
+        // and go back to the old one
+        h.checkout_impl(2).unwrap();
+        assert_eq!(h.current_seq().seq, b"acg");
+        assert!(h.undo_impl());
+        assert!(h.redo_impl());
+        assert_eq!(h.current_seq().seq, b"acg");
+        assert!(h.checkout_impl(10).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut h = SeqHistory::from_seq(seq(b"a"));
+        h.record(op("insert"), seq(b"ac"));
+        h.record(op("insert"), seq(b"acg"));
+        h.undo_impl();
+        let decoded = SeqHistory::decode(&h.encode().unwrap()).unwrap();
+        assert_eq!(decoded.current, 1);
+        assert_eq!(decoded.states.len(), 3);
+        assert_eq!(decoded.states[2].op, op("insert"));
+        assert_eq!(decoded.states[2].seq.seq, b"acg");
+        assert_eq!(decoded.states[1].redo, Some(2));
+        assert!(SeqHistory::decode(b"CLNHIST\x00\x02\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_shared_seqs() {
+        let bases: Vec<u8> = b"acgt".iter().cycle().take(10_000).cloned().collect();
+        let first = seq(&bases);
+        let mut h = SeqHistory::from_seq(first.clone());
+        // only the features change
+        let annotated = Rc::new(Seq {
+            features: vec![Feature {
+                kind: feature_kind!("gene"),
+                location: Location::simple_range(0, 10),
+                qualifiers: Vec::new(),
+            }],
+            ..(*first).clone()
+        });
+        h.record(op("annotate"), annotated.clone());
+        h.record(op("revert"), first.clone());
+        let single = SeqHistory::from_seq(first).encode().unwrap();
+        let encoded = h.encode().unwrap();
+        assert!(encoded.len() < single.len() + 500);
+        let decoded = SeqHistory::decode(&encoded).unwrap();
+        assert!(Rc::ptr_eq(&decoded.states[0].seq, &decoded.states[2].seq));
+        assert_eq!(decoded.states[0].seq.seq, bases);
+        assert_eq!(*decoded.states[1].seq, *annotated);
+        // redo has to lead to a child
+        h.states[0].redo = Some(2);
+        assert!(SeqHistory::decode(&h.encode().unwrap()).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::bed;
 use crate::utils::{future_to_promise, wait};
 use crate::JsSeq;
 use gb_io::seq::*;
@@ -10,18 +11,18 @@ use wasm_bindgen::prelude::*;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename = "Primer")]
-struct JsPrimerRef {
-    name: String,
-    seq: String,
-    desc: String,
+pub(crate) struct JsPrimerRef {
+    pub(crate) name: String,
+    pub(crate) seq: String,
+    pub(crate) desc: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, TypescriptDefinition)]
 #[serde(rename_all = "camelCase")]
-struct PcrSettings {
-    min_fp: i64,
-    min_len: i64,
-    max_len: i64,
+pub(crate) struct PcrSettings {
+    pub(crate) min_fp: i64,
+    pub(crate) min_len: i64,
+    pub(crate) max_len: i64,
 }
 
 impl Primer for JsPrimerRef {
@@ -281,6 +282,53 @@ impl PcrResults {
 
     }
 
+    /// Export the products as BED, or their coverage as bedGraph. Products
+    /// without a length in this template are left out.
+    pub fn products_to_bed(&self, bedgraph: bool) -> Vec<u8> {
+        let intervals: Vec<_> = self
+            .products
+            .iter()
+            .filter_map(|p| {
+                Some(bed::Interval {
+                    start: p.0.start,
+                    end: p.0.start + p.len(&self.seq)?,
+                    name: format!("{}_{}", p.0.primer.name(), p.1.primer.name()),
+                    fwd: None,
+                })
+            })
+            .collect();
+        let mut data = Vec::new();
+        bed::write(&mut data, &self.seq, "PCR products", &intervals, bedgraph).unwrap();
+        data
+    }
+
+    /// Export the primer binding sites as BED, or their coverage as bedGraph
+    pub fn matches_to_bed(&self, bedgraph: bool) -> Vec<u8> {
+        let mut intervals = Vec::new();
+        if let Some(matches) = &self.matches {
+            for m in &matches.fwd {
+                intervals.push(bed::Interval {
+                    start: m.start,
+                    end: m.start + m.len().abs(),
+                    name: m.primer.name().to_string(),
+                    fwd: Some(true),
+                });
+            }
+            // reverse matches start at the 5' end of the primer
+            for m in &matches.rev {
+                intervals.push(bed::Interval {
+                    start: m.start - m.len().abs() + 1,
+                    end: m.start + 1,
+                    name: m.primer.name().to_string(),
+                    fwd: Some(false),
+                });
+            }
+        }
+        let mut data = Vec::new();
+        bed::write(&mut data, &self.seq, "primer binding", &intervals, bedgraph).unwrap();
+        data
+    }
+
     pub fn run(&self, settings: &JsValue) -> JsPcrer {
         let settings: PcrSettings = settings.into_serde().unwrap();
         let matches = if settings.min_fp == self.settings.min_fp {
@@ -291,3 +339,55 @@ impl PcrResults {
         JsPcrer::new_from_rust(self.seq.clone(), self.primers.clone(), settings, matches)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn primer(name: &str, seq: &str) -> JsPrimerRef {
+        JsPrimerRef {
+            name: name.into(),
+            seq: seq.into(),
+            desc: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_to_bed() {
+        // the product crosses the origin, the primers don't
+        let seq = Rc::new(Seq {
+            name: Some("template".into()),
+            seq: b"GCTAAAGACAATTACATAACATACACGTCAGCACGAAACT".to_vec(),
+            topology: Topology::Circular,
+            ..Seq::empty()
+        });
+        let primers = vec![primer("f", "CAGCACGAAA"), primer("r", "ATTGTCTTTA")];
+        let matches = pcr::find_matches(&seq, primers.iter().cloned(), 10, pcr::Method::Bndm);
+        let products: Vec<_> = matches.find_products(&*seq, 0, 1000).collect();
+        assert_eq!(products.len(), 1);
+        let results = PcrResults {
+            seq: seq.clone(),
+            primers: Rc::new(primers),
+            matches: Some(Rc::new(matches)),
+            products,
+            settings: PcrSettings {
+                min_fp: 10,
+                min_len: 0,
+                max_len: 1000,
+            },
+        };
+        assert_eq!(
+            String::from_utf8(results.products_to_bed(false)).unwrap(),
+            "template\t28\t40\tf_r\t0\t.\ntemplate\t0\t12\tf_r\t0\t.\n"
+        );
+        assert_eq!(
+            String::from_utf8(results.matches_to_bed(false)).unwrap(),
+            "template\t28\t38\tf\t0\t+\ntemplate\t2\t12\tr\t0\t-\n"
+        );
+        assert_eq!(
+            String::from_utf8(results.products_to_bed(true)).unwrap(),
+            "track type=bedGraph name=\"PCR products\"\n\
+             template\t0\t12\t1\ntemplate\t28\t40\t1\n"
+        );
+    }
+}
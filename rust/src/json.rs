@@ -0,0 +1,405 @@
+//! JSON import/export of sequences. Unlike the `serde` representation of
+//! `gb_io::seq::Seq` (which changes whenever `gb-io` does) this schema is
+//! stable, any incompatible change gets a new `version`.
+//!
+//! # Schema, version 1
+//!
+//! ```text
+//! {
+//!   "schema": "clonifier-seq",
+//!   "version": 1,
+//!   "name": string | null,
+//!   "topology": "linear" | "circular",
+//!   "moleculeType": string | null,   // e.g. "DNA"
+//!   "division": string,              // e.g. "SYN", may be ""
+//!   "definition": string | null,
+//!   "accession": string | null,
+//!   "accessionVersion": string | null,
+//!   "keywords": string | null,
+//!   "source": { "source": string, "organism": string | null } | null,
+//!   "comments": [string],
+//!   "seq": string,
+//!   "features": [{
+//!     "kind": string,                // e.g. "CDS"
+//!     "location": Location,
+//!     "qualifiers": [{ "key": string, "value": string | null }]
+//!   }]
+//! }
+//! ```
+//!
+//! Coordinates are 0-based and ranges are half-open, so the GenBank location
+//! `1..10` is `{"type": "range", "start": 0, "end": 10}`. A `Location` is one
+//! of:
+//!
+//! ```text
+//! { "type": "range", "start": int, "end": int,
+//!   "before": bool, "after": bool }      // `<` and `>`, both default to false
+//! { "type": "between", "left": int, "right": int }   // `left^right`, 1-based
+//! { "type": "complement", "location": Location }
+//! { "type": "join" | "order" | "bond" | "oneOf", "locations": [Location] }
+//! { "type": "external", "accession": string, "location": Location | null }
+//! { "type": "gap", "length": int | null }
+//! ```
+//!
+//! On import `schema` is optional, `version` is required. Fields which are
+//! missing are treated as `null`/empty, except `seq`. A file may contain a
+//! single sequence or an array of them. Dates, references, DBLINK and CONTIG
+//! are not part of the schema.
+use gb_io::seq::*;
+
+pub const SCHEMA: &str = "clonifier-seq";
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum JsonTopology {
+    Linear,
+    Circular,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum JsonLocation {
+    Range {
+        start: i64,
+        end: i64,
+        #[serde(default)]
+        before: bool,
+        #[serde(default)]
+        after: bool,
+    },
+    Between {
+        left: i64,
+        right: i64,
+    },
+    Complement {
+        location: Box<JsonLocation>,
+    },
+    Join {
+        locations: Vec<JsonLocation>,
+    },
+    Order {
+        locations: Vec<JsonLocation>,
+    },
+    Bond {
+        locations: Vec<JsonLocation>,
+    },
+    OneOf {
+        locations: Vec<JsonLocation>,
+    },
+    External {
+        accession: String,
+        #[serde(default)]
+        location: Option<Box<JsonLocation>>,
+    },
+    Gap {
+        #[serde(default)]
+        length: Option<i64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct JsonQualifier {
+    key: String,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct JsonFeature {
+    kind: String,
+    location: JsonLocation,
+    #[serde(default)]
+    qualifiers: Vec<JsonQualifier>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct JsonSource {
+    source: String,
+    #[serde(default)]
+    organism: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JsonSeq {
+    #[serde(default)]
+    schema: Option<String>,
+    version: u32,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default = "default_topology")]
+    topology: JsonTopology,
+    #[serde(default)]
+    molecule_type: Option<String>,
+    #[serde(default)]
+    division: String,
+    #[serde(default)]
+    definition: Option<String>,
+    #[serde(default)]
+    accession: Option<String>,
+    #[serde(default)]
+    accession_version: Option<String>,
+    #[serde(default)]
+    keywords: Option<String>,
+    #[serde(default)]
+    source: Option<JsonSource>,
+    #[serde(default)]
+    comments: Vec<String>,
+    seq: String,
+    #[serde(default)]
+    features: Vec<JsonFeature>,
+}
+
+fn default_topology() -> JsonTopology {
+    JsonTopology::Linear
+}
+
+fn location_to_json(l: &Location) -> JsonLocation {
+    use Location::*;
+    let list = |ls: &[Location]| ls.iter().map(location_to_json).collect();
+    match *l {
+        Range((start, Before(before)), (end, After(after))) => JsonLocation::Range {
+            start,
+            end,
+            before,
+            after,
+        },
+        Between(left, right) => JsonLocation::Between { left, right },
+        Complement(ref l) => JsonLocation::Complement {
+            location: Box::new(location_to_json(l)),
+        },
+        Join(ref ls) => JsonLocation::Join {
+            locations: list(ls),
+        },
+        Order(ref ls) => JsonLocation::Order {
+            locations: list(ls),
+        },
+        Bond(ref ls) => JsonLocation::Bond {
+            locations: list(ls),
+        },
+        OneOf(ref ls) => JsonLocation::OneOf {
+            locations: list(ls),
+        },
+        External(ref accession, ref l) => JsonLocation::External {
+            accession: accession.clone(),
+            location: l.as_ref().map(|l| Box::new(location_to_json(l))),
+        },
+        Gap(GapLength::Known(len)) => JsonLocation::Gap { length: Some(len) },
+        Gap(_) => JsonLocation::Gap { length: None },
+    }
+}
+
+fn location_from_json(l: JsonLocation) -> Result<Location, String> {
+    use Location::*;
+    let list = |ls: Vec<JsonLocation>| -> Result<Vec<Location>, String> {
+        ls.into_iter().map(location_from_json).collect()
+    };
+    Ok(match l {
+        JsonLocation::Range {
+            start,
+            end,
+            before,
+            after,
+        } => {
+            if start < 0 || end < start {
+                return Err(format!("Invalid range: {}..{}", start, end));
+            }
+            Range((start, Before(before)), (end, After(after)))
+        }
+        JsonLocation::Between { left, right } => Between(left, right),
+        JsonLocation::Complement { location } => {
+            Complement(Box::new(location_from_json(*location)?))
+        }
+        JsonLocation::Join { locations } => Join(list(locations)?),
+        JsonLocation::Order { locations } => Order(list(locations)?),
+        JsonLocation::Bond { locations } => Bond(list(locations)?),
+        JsonLocation::OneOf { locations } => OneOf(list(locations)?),
+        JsonLocation::External {
+            accession,
+            location,
+        } => External(
+            accession,
+            match location {
+                Some(l) => Some(Box::new(location_from_json(*l)?)),
+                None => None,
+            },
+        ),
+        JsonLocation::Gap { length: Some(len) } => Gap(GapLength::Known(len)),
+        JsonLocation::Gap { length: None } => Gap(GapLength::Unknown),
+    })
+}
+
+fn to_json_seq(seq: &Seq) -> JsonSeq {
+    JsonSeq {
+        schema: Some(SCHEMA.into()),
+        version: JSON_SCHEMA_VERSION,
+        name: seq.name.clone(),
+        topology: if seq.is_circular() {
+            JsonTopology::Circular
+        } else {
+            JsonTopology::Linear
+        },
+        molecule_type: seq.molecule_type.clone(),
+        division: seq.division.clone(),
+        definition: seq.definition.clone(),
+        accession: seq.accession.clone(),
+        accession_version: seq.version.clone(),
+        keywords: seq.keywords.clone(),
+        source: seq.source.as_ref().map(|s| JsonSource {
+            source: s.source.clone(),
+            organism: s.organism.clone(),
+        }),
+        comments: seq.comments.clone(),
+        seq: String::from_utf8_lossy(&seq.seq).into_owned(),
+        features: seq
+            .features
+            .iter()
+            .map(|f| {
+                let kind: &str = &f.kind;
+                JsonFeature {
+                    kind: kind.to_string(),
+                    location: location_to_json(&f.location),
+                    qualifiers: f
+                        .qualifiers
+                        .iter()
+                        .map(|(k, v)| {
+                            let key: &str = k;
+                            JsonQualifier {
+                                key: key.to_string(),
+                                value: v.clone(),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn from_json_seq(s: JsonSeq) -> Result<Seq, String> {
+    if let Some(schema) = &s.schema {
+        if schema != SCHEMA {
+            return Err(format!("Unknown schema: {}", schema));
+        }
+    }
+    if s.version != JSON_SCHEMA_VERSION {
+        return Err(format!("Can't read version {} of JSON schema", s.version));
+    }
+    if let Some(c) = s.seq.chars().find(|c| !c.is_ascii_alphabetic()) {
+        return Err(format!("Invalid character in sequence: {:?}", c));
+    }
+    let features = s
+        .features
+        .into_iter()
+        .map(|f| {
+            Ok(Feature {
+                kind: FeatureKind::from(f.kind.as_str()),
+                location: location_from_json(f.location)?,
+                qualifiers: f
+                    .qualifiers
+                    .into_iter()
+                    .map(|q| (QualifierKey::from(q.key.as_str()), q.value))
+                    .collect(),
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Seq {
+        name: s.name,
+        topology: match s.topology {
+            JsonTopology::Linear => Topology::Linear,
+            JsonTopology::Circular => Topology::Circular,
+        },
+        molecule_type: s.molecule_type,
+        division: s.division,
+        definition: s.definition,
+        accession: s.accession,
+        version: s.accession_version,
+        keywords: s.keywords,
+        source: s.source.map(|s| Source {
+            source: s.source,
+            organism: s.organism,
+        }),
+        comments: s.comments,
+        seq: s.seq.into_bytes(),
+        features,
+        ..Seq::empty()
+    })
+}
+
+pub fn write(seq: &Seq) -> String {
+    serde_json::to_string_pretty(&to_json_seq(seq)).expect("Serializing JSON failed")
+}
+
+pub fn write_all<'a>(seqs: impl IntoIterator<Item = &'a Seq>) -> String {
+    let seqs: Vec<_> = seqs.into_iter().map(to_json_seq).collect();
+    serde_json::to_string_pretty(&seqs).expect("Serializing JSON failed")
+}
+
+/// Parse a single sequence or an array of sequences
+pub fn parse(data: &[u8]) -> Result<Vec<Seq>, String> {
+    let value: serde_json::Value =
+        serde_json::from_slice(data).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let values = match value {
+        serde_json::Value::Array(values) => values,
+        value => vec![value],
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let s: JsonSeq =
+                serde_json::from_value(v).map_err(|e| format!("Sequence {}: {}", i, e))?;
+            from_json_seq(s).map_err(|e| format!("Sequence {}: {}", i, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    #[test]
+    fn test_round_trip() {
+        let seq = Seq {
+            name: Some("test".into()),
+            topology: Topology::Circular,
+            molecule_type: Some("DNA".into()),
+            division: "SYN".into(),
+            definition: Some("A test".into()),
+            comments: vec!["hello".into()],
+            seq: b"acgtacgtacgtacgtacgt".to_vec(),
+            features: vec![Feature {
+                kind: feature_kind!("CDS"),
+                location: location::parse("complement(join(<1..5,10..>12))").unwrap(),
+                qualifiers: vec![
+                    (qualifier_key!("gene"), Some("abc".into())),
+                    (QualifierKey::from("pseudo"), None),
+                ],
+            }],
+            ..Seq::empty()
+        };
+        let json = write(&seq);
+        assert_eq!(parse(json.as_bytes()).unwrap(), vec![seq.clone()]);
+        let json = write_all(vec![&seq, &seq]);
+        assert_eq!(parse(json.as_bytes()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        let json = r#"{
+            "version": 1,
+            "seq": "acgtacgt",
+            "features": [{
+                "kind": "misc_feature",
+                "location": {"type": "range", "start": 0, "end": 4}
+            }]
+        }"#;
+        let seqs = parse(json.as_bytes()).unwrap();
+        assert!(!seqs[0].is_circular());
+        assert_eq!(location::format(&seqs[0].features[0].location), "1..4");
+        assert!(parse(br#"{"version": 2, "seq": ""}"#).is_err());
+        assert!(parse(br#"{"version": 1, "seq": "ac gt"}"#).is_err());
+    }
+}
@@ -8,28 +8,48 @@ use std::panic;
 use std::rc::Rc;
 use std::str;
 
-use bincode::{deserialize_from, serialize_into};
-use bio::io::fasta;
 use gb_io::{reader::SeqReader, seq::*};
 
-
-#[derive(Serialize, Deserialize, PartialEq)]
-struct BinSeqVersion(u32);
-
-// update this if/when `gb-io::Seq` breaks compatibility
-const BIN_SEQ_FORMAT_VERSION: BinSeqVersion = BinSeqVersion(2);
-
 use js_sys::{Error, JsString, RegExp};
 
+use serde::de::DeserializeOwned;
+
 use wasm_bindgen::prelude::*;
 
+mod annotate;
 mod arc;
+mod archive;
 mod assembly;
 mod assembly_diagram;
+mod bed;
+mod bin_format;
+mod concat;
+mod detect;
+mod diff;
+mod edit;
+mod embl;
+mod enzymes;
+mod fasta;
+mod feature_edit;
+mod feature_library;
+mod gb_lenient;
+mod genetic_code;
+mod gff;
+mod history;
 mod js_pcr;
+mod json;
+mod location;
 mod logger;
+mod orf;
+mod primer_table;
+mod project;
+mod restriction;
+mod sbol;
 mod search;
 mod seq_diagram;
+mod snapgene;
+mod transfer;
+mod translate;
 mod utils;
 
 pub use crate::assembly::*;
@@ -53,51 +73,269 @@ pub fn init() {
     logger::Logger::init().unwrap();
 }
 
-#[wasm_bindgen]
-pub fn parse_gb(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
-    let mut res = Vec::new();
-    for seq in SeqReader::new(data) {
-        res.push(JsValue::from(JsSeq(Rc::new(
-            seq.map_err(|e| Error::new(&format!("Parsing failed: {}", e)))?,
-        ))));
-    }
-    Ok(res.into_boxed_slice())
+fn read_gb(data: &[u8]) -> Result<Vec<Seq>, String> {
+    SeqReader::new(data)
+        .map(|seq| seq.map_err(|e| format!("Parsing failed: {}", e)))
+        .collect()
 }
 
-#[wasm_bindgen]
-pub fn parse_fasta(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
+fn read_fasta(data: &[u8]) -> Result<Vec<Seq>, String> {
     let mut res = Vec::new();
-    for r in fasta::Reader::new(data).records() {
-        let r = r.map_err(|e| Error::new(&format!("Parsing failed: {}", e)))?;
-        r.check()
-            .map_err(|e| Error::new(&format!("Invalid sequence: {}", e)))?;
+    for (idx, r) in bio::io::fasta::Reader::new(data).records().enumerate() {
+        let r = r.map_err(|e| format!("Parsing failed: {}", e))?;
+        if let Err(e) = r.check() {
+            // `check` doesn't say where, so look up the offending character
+            let issue = fasta::read_validated(data, &Default::default())
+                .ok()
+                .and_then(|v| {
+                    v.issues
+                        .into_iter()
+                        .find(|i| i.record == idx && !i.character.is_ascii())
+                });
+            return Err(match issue {
+                Some(issue) => format!("Invalid sequence: {}", issue),
+                None => format!("Invalid sequence: {}", e),
+            });
+        }
         let seq = Seq {
             seq: r.seq().into(),
             name: Some(r.id().into()),
             ..Seq::empty()
         };
-        res.push(JsValue::from(JsSeq(Rc::new(seq))));
+        res.push(seq);
+    }
+    Ok(res)
+}
+
+fn to_js_seqs(seqs: Result<Vec<Seq>, String>) -> Result<Box<[JsValue]>, JsValue> {
+    Ok(seqs
+        .map_err(|e| Error::new(&e))?
+        .into_iter()
+        .map(|seq| JsValue::from(JsSeq(Rc::new(seq))))
+        .collect::<Vec<_>>()
+        .into_boxed_slice())
+}
+
+#[wasm_bindgen]
+pub fn parse_gb(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
+    to_js_seqs(archive::maybe_gunzip(data).and_then(|d| read_gb(&d)))
+}
+
+/// The result of `parse_gb_lenient`
+#[wasm_bindgen]
+pub struct LenientParseResult {
+    seqs: Vec<Rc<Seq>>,
+    warnings: Vec<gb_lenient::Warning>,
+}
+
+#[wasm_bindgen]
+impl LenientParseResult {
+    pub fn get_seqs(&self) -> Box<[JsValue]> {
+        self.seqs
+            .iter()
+            .map(|s| JsValue::from(JsSeq(s.clone())))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+    pub fn get_warnings(&self) -> JsValue {
+        JsValue::from_serde(&self.warnings).unwrap()
+    }
+}
+
+/// Like `parse_gb`, but records or features which can't be parsed are skipped
+/// and reported as warnings
+#[wasm_bindgen]
+pub fn parse_gb_lenient(data: &[u8]) -> Result<LenientParseResult, JsValue> {
+    let data = archive::maybe_gunzip(data).map_err(|e| Error::new(&e))?;
+    let (seqs, warnings) = gb_lenient::parse(&data);
+    Ok(LenientParseResult {
+        seqs: seqs.into_iter().map(Rc::new).collect(),
+        warnings,
+    })
+}
+
+#[wasm_bindgen]
+pub fn parse_fasta(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
+    to_js_seqs(archive::maybe_gunzip(data).and_then(|d| read_fasta(&d)))
+}
+
+/// The result of `validate_fasta`
+#[wasm_bindgen]
+pub struct FastaValidation(fasta::Validated);
+
+#[wasm_bindgen]
+impl FastaValidation {
+    pub fn get_seqs(&self) -> Box<[JsValue]> {
+        self.0
+            .seqs
+            .iter()
+            .map(|s| JsValue::from(JsSeq(Rc::new(s.clone()))))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+    /// At most `fasta::MAX_ISSUES`, see `get_total_issues`
+    pub fn get_issues(&self) -> JsValue {
+        JsValue::from_serde(&self.0.issues).unwrap()
+    }
+    pub fn get_total_issues(&self) -> usize {
+        self.0.total_issues
+    }
+    pub fn get_rejected(&self) -> JsValue {
+        JsValue::from_serde(&self.0.rejected).unwrap()
     }
-    Ok(res.into_boxed_slice())
 }
 
+/// A stricter `parse_fasta`, which only accepts IUPAC codes and reports
+/// every invalid character and gap. These are handled according to
+/// `settings` (a `fasta::ValidationSettings`, or undefined to reject records
+/// containing either).
 #[wasm_bindgen]
-pub fn parse_bin(mut data: &[u8]) -> Result<JsSeq, JsValue> {
-    // read version tag
-    let ver: BinSeqVersion = deserialize_from(&mut data)
-        .map_err(|e| Error::new(&format!("Decoding header failed: {}", e)))?;
-    if ver != BIN_SEQ_FORMAT_VERSION {
-        return Err(Error::new(&format!(
-            "Can't decode version {} of binary sequence format",
-            ver.0
-        ))
-        .into());
-    }
-    let seq =
-        deserialize_from(&mut data).map_err(|e| Error::new(&format!("Decoding failed: {}", e)))?;
+pub fn validate_fasta(data: &[u8], settings: &JsValue) -> Result<FastaValidation, JsValue> {
+    let settings: fasta::ValidationSettings = parse_settings(settings)?;
+    let data = archive::maybe_gunzip(data).map_err(|e| Error::new(&e))?;
+    let res = fasta::read_validated(&data, &settings).map_err(|e| Error::new(&e))?;
+    Ok(FastaValidation(res))
+}
+
+#[wasm_bindgen]
+pub fn parse_embl(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
+    to_js_seqs(
+        archive::maybe_gunzip(data)
+            .and_then(|d| embl::parse(&d).map_err(|e| format!("Parsing failed: {}", e))),
+    )
+}
+
+/// Parse sequences in the JSON schema documented in `json.rs`
+#[wasm_bindgen]
+pub fn parse_json(data: &[u8]) -> Result<Box<[JsValue]>, JsValue> {
+    to_js_seqs(
+        archive::maybe_gunzip(data)
+            .and_then(|d| json::parse(&d).map_err(|e| format!("Parsing failed: {}", e))),
+    )
+}
+
+#[wasm_bindgen]
+pub fn parse_snapgene(data: &[u8]) -> Result<JsSeq, JsValue> {
+    let seq = archive::maybe_gunzip(data)
+        .and_then(|d| snapgene::parse(&d).map_err(|e| format!("Parsing failed: {}", e)))
+        .map_err(|e| Error::new(&e))?;
+    Ok(JsSeq(Rc::new(seq)))
+}
+
+#[wasm_bindgen]
+pub fn parse_bin(data: &[u8]) -> Result<JsSeq, JsValue> {
+    let seq = bin_format::decode(data).map_err(|e| Error::new(&e))?;
     Ok(JsSeq(Rc::new(seq)))
 }
 
+/// A file (or archive entry) that couldn't be parsed
+#[derive(Serialize, Debug)]
+struct EntryError {
+    entry: String,
+    message: String,
+}
+
+/// The result of `parse_any`
+#[wasm_bindgen]
+pub struct ParsedFile {
+    format: detect::Format,
+    seqs: Vec<Rc<Seq>>,
+    errors: Vec<EntryError>,
+}
+
+#[wasm_bindgen]
+impl ParsedFile {
+    pub fn get_format(&self) -> JsValue {
+        JsValue::from_serde(&self.format).unwrap()
+    }
+    pub fn get_seqs(&self) -> Box<[JsValue]> {
+        self.seqs
+            .iter()
+            .map(|s| JsValue::from(JsSeq(s.clone())))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+    /// Errors for the entries of an archive which couldn't be parsed
+    pub fn get_errors(&self) -> JsValue {
+        JsValue::from_serde(&self.errors).unwrap()
+    }
+}
+
+/// How deeply archives may be nested in each other, a file decompressing
+/// to itself would otherwise never finish
+const MAX_ARCHIVE_DEPTH: usize = 4;
+
+/// Parse a file of any format. For gzipped files the format returned is that
+/// of the decompressed file.
+fn read_any(data: &[u8]) -> Result<(detect::Format, Vec<Seq>, Vec<EntryError>), String> {
+    read_nested(data, 0)
+}
+
+fn read_nested(
+    data: &[u8],
+    depth: usize,
+) -> Result<(detect::Format, Vec<Seq>, Vec<EntryError>), String> {
+    use detect::Format::*;
+    let mut format = detect::detect(data).ok_or_else(|| String::from("Unknown file format"))?;
+    if (format == Gzip || format == Zip) && depth >= MAX_ARCHIVE_DEPTH {
+        return Err("Archives are nested too deeply".into());
+    }
+    let mut errors = Vec::new();
+    let seqs = match format {
+        GenBank => read_gb(data)?,
+        Fasta => read_fasta(data)?,
+        Embl => embl::parse(data).map_err(|e| format!("Parsing failed: {}", e))?,
+        SnapGene => vec![snapgene::parse(data).map_err(|e| format!("Parsing failed: {}", e))?],
+        Bin => vec![bin_format::decode(data)?],
+        Json => json::parse(data).map_err(|e| format!("Parsing failed: {}", e))?,
+        Project => project::Project::decode(data)?
+            .seqs()
+            .iter()
+            .map(|s| (**s).clone())
+            .collect(),
+        Gzip => {
+            let (inner, seqs, inner_errors) = read_nested(&archive::gunzip(data)?, depth + 1)?;
+            format = inner;
+            errors = inner_errors;
+            seqs
+        }
+        Zip => {
+            let mut seqs = Vec::new();
+            for (name, contents) in archive::unzip(data)? {
+                match contents.and_then(|c| read_nested(&c, depth + 1)) {
+                    Ok((_, entry_seqs, entry_errors)) => {
+                        seqs.extend(entry_seqs);
+                        // nested archives
+                        errors.extend(entry_errors.into_iter().map(|e| EntryError {
+                            entry: format!("{}/{}", name, e.entry),
+                            message: e.message,
+                        }));
+                    }
+                    Err(message) => errors.push(EntryError {
+                        entry: name,
+                        message,
+                    }),
+                }
+            }
+            seqs
+        }
+    };
+    Ok((format, seqs, errors))
+}
+
+/// Parse a file in any of the supported formats, detecting the format from
+/// its contents. Gzipped files and zip archives are decompressed, for zip
+/// archives one entry failing to parse doesn't fail the whole file.
+#[wasm_bindgen]
+pub fn parse_any(data: &[u8]) -> Result<ParsedFile, JsValue> {
+    let (format, seqs, errors) = read_any(data).map_err(|e| Error::new(&e))?;
+    Ok(ParsedFile {
+        format,
+        seqs: seqs.into_iter().map(Rc::new).collect(),
+        errors,
+    })
+}
+
 #[wasm_bindgen(js_name = Seq)]
 pub struct JsSeq(Rc<Seq>);
 
@@ -144,15 +382,7 @@ impl JsSeq {
         JsSeq(Rc::new(self.0.revcomp()))
     }
     pub fn to_bin(&self) -> Result<Vec<u8>, JsValue> {
-        let mut res = Vec::new();
-        let mut buf = &mut res;
-        // write version tag
-        serialize_into(&mut buf, &BIN_SEQ_FORMAT_VERSION).expect("Writing header failed");
-        let seq: &Seq = &self.0;
-        // can this actually fail?
-        serialize_into(&mut buf, seq)
-            .map_err(|e| Error::new(&format!("Couldn't encode: {}", e)))?;
-        Ok(res)
+        bin_format::encode(&self.0).map_err(|e| Error::new(&e).into())
     }
     pub fn to_gb(&self) -> Vec<u8> {
         let seq: &Seq = &self.0;
@@ -160,6 +390,36 @@ impl JsSeq {
         seq.write(&mut data).unwrap();
         data
     }
+    /// `line_width` 0 means no wrapping, see `fasta::format_header` for the
+    /// placeholders supported in `header`
+    pub fn to_fasta(&self, line_width: usize, header: Option<String>) -> Vec<u8> {
+        let header = header.as_ref().map(String::as_str).unwrap_or(fasta::DEFAULT_HEADER);
+        let mut data = Vec::new();
+        fasta::write(&mut data, &self.0, line_width, header).unwrap();
+        data
+    }
+    pub fn to_embl(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        embl::write(&mut data, &self.0).unwrap();
+        data
+    }
+    /// Export using the JSON schema documented in `json.rs`
+    pub fn to_json(&self) -> String {
+        json::write(&self.0)
+    }
+    pub fn to_gff3(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        gff::write(&mut data, &self.0).unwrap();
+        data
+    }
+    /// Add the features from a GFF3 file whose seqid matches this sequence's name
+    pub fn merge_gff3(&self, data: &[u8]) -> Result<JsSeq, JsValue> {
+        let features = gff::parse_features(data, &self.0)
+            .map_err(|e| Error::new(&format!("Parsing failed: {}", e)))?;
+        let mut res = Seq::clone(&self.0);
+        res.features.extend(features);
+        Ok(JsSeq(Rc::new(res)))
+    }
     pub fn get_feature_count(&self) -> u32 {
         self.0.features.len() as u32
     }
@@ -183,6 +443,48 @@ impl JsSeq {
     pub fn get_feature_qualifiers(&self, idx: usize) -> JsValue {
         JsValue::from_serde(&self.0.features[idx].qualifiers).unwrap()
     }
+    /// Add a feature, `location` uses GenBank syntax (e.g.
+    /// `complement(join(1..10,20..30))`) and `qualifiers` is an array of
+    /// `[key, value]` pairs, as returned by `get_feature_qualifiers`
+    pub fn add_feature(
+        &self,
+        kind: &str,
+        location: &str,
+        qualifiers: &JsValue,
+    ) -> Result<JsSeq, JsValue> {
+        let location = parse_location(location)?;
+        let qualifiers = parse_qualifiers(qualifiers)?;
+        let seq = feature_edit::add(&self.0, kind, location, qualifiers).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    pub fn set_feature_kind(&self, idx: usize, kind: &str) -> Result<JsSeq, JsValue> {
+        let seq = feature_edit::set_kind(&self.0, idx, kind).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    pub fn set_feature_location(&self, idx: usize, location: &str) -> Result<JsSeq, JsValue> {
+        let location = parse_location(location)?;
+        let seq =
+            feature_edit::set_location(&self.0, idx, location).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    pub fn set_feature_qualifiers(&self, idx: usize, qualifiers: &JsValue) -> Result<JsSeq, JsValue> {
+        let qualifiers = parse_qualifiers(qualifiers)?;
+        let seq = feature_edit::set_qualifiers(&self.0, idx, qualifiers)
+            .map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    /// The location of a feature in GenBank syntax, for editing
+    pub fn get_feature_location(&self, idx: usize) -> Option<String> {
+        self.0
+            .features
+            .get(idx)
+            .map(|f| location::format(&f.location))
+    }
+    pub fn delete_features(&self, idxes: Vec<u32>) -> Result<JsSeq, JsValue> {
+        let idxes: Vec<usize> = idxes.into_iter().map(|i| i as usize).collect();
+        let seq = feature_edit::delete(&self.0, &idxes).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
 
     pub fn get_feature_qualifier(&self, qualifier: &str) -> Box<[JsValue]> {
         let key = QualifierKey::from(qualifier);
@@ -226,6 +528,89 @@ impl JsSeq {
             .collect::<Vec<_>>()
             .into_boxed_slice()
     }
+    /// Export the features as BED, or their coverage as bedGraph
+    pub fn features_to_bed(&self, bedgraph: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        bed::write(&mut data, &self.0, "features", &bed::features(&self.0), bedgraph).unwrap();
+        data
+    }
+    /// Export the hits of a sequence search (as in `search_seq`) as BED or
+    /// bedGraph
+    pub fn search_to_bed(&self, query: &str, max_res: usize, bedgraph: bool) -> Vec<u8> {
+        // leave out the extra result marking a truncated search
+        let results: Vec<_> = search::search(&self.0, query.as_bytes(), max_res, true)
+            .into_iter()
+            .take(max_res)
+            .collect();
+        let intervals = bed::search_results(&results, query);
+        let mut data = Vec::new();
+        bed::write(&mut data, &self.0, query, &intervals, bedgraph).unwrap();
+        data
+    }
+    /// Differences from this sequence to `sample`, see `diff::Diff`
+    pub fn diff(&self, sample: &JsSeq) -> Result<JsValue, JsValue> {
+        let res = diff::diff(&self.0, &sample.0).map_err(|e| Error::new(&e))?;
+        Ok(JsValue::from_serde(&res).unwrap())
+    }
+    /// Copy the features of `source` onto homologous regions of this
+    /// sequence, `settings` is a `transfer::TransferSettings`
+    pub fn transfer_features(&self, source: &JsSeq, settings: &JsValue) -> Result<JsSeq, JsValue> {
+        let settings: transfer::TransferSettings = parse_settings(settings)?;
+        Ok(JsSeq(Rc::new(transfer::transfer(&source.0, &self.0, &settings))))
+    }
+    /// Add features for the elements of the built-in feature library found
+    /// in this sequence, `settings` is an `annotate::AnnotateSettings`
+    pub fn auto_annotate(&self, settings: &JsValue) -> Result<JsSeq, JsValue> {
+        let settings: annotate::AnnotateSettings = parse_settings(settings)?;
+        let seq = annotate::auto_annotate(&self.0, &settings).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    /// Open reading frames, `settings` is an `orf::OrfSettings`
+    pub fn find_orfs(&self, settings: &JsValue) -> Result<JsValue, JsValue> {
+        let settings: orf::OrfSettings = parse_settings(settings)?;
+        let orfs = orf::find(&self.0, &settings).map_err(|e| Error::new(&e))?;
+        Ok(JsValue::from_serde(&orfs).unwrap())
+    }
+    /// Add a `CDS` feature for each open reading frame
+    pub fn annotate_orfs(&self, settings: &JsValue) -> Result<JsSeq, JsValue> {
+        let settings: orf::OrfSettings = parse_settings(settings)?;
+        let orfs = orf::find(&self.0, &settings).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(orf::annotate(&self.0, &orfs, settings.table))))
+    }
+    /// The sites of each enzyme with their cuts, `settings` is a
+    /// `restriction::RestrictionSettings`
+    pub fn restriction_sites(&self, settings: &JsValue) -> Result<JsValue, JsValue> {
+        let sites = restriction_map(&self.0, settings)?;
+        Ok(JsValue::from_serde(&sites).unwrap())
+    }
+    /// The enzymes cutting once, twice or not at all
+    pub fn restriction_summary(&self, settings: &JsValue) -> Result<JsValue, JsValue> {
+        let sites = restriction_map(&self.0, settings)?;
+        Ok(JsValue::from_serde(&restriction::summary(&sites)).unwrap())
+    }
+    /// Translate `start..end` with NCBI table `table`, reverse complemented
+    /// unless `fwd`
+    pub fn translate_range(&self, start: i32, end: i32, fwd: bool, table: u8) -> Result<String, JsValue> {
+        translate::translate_range(&self.0, start as i64, end as i64, fwd, table)
+            .map_err(|e| Error::new(&e).into())
+    }
+    /// The protein of a feature, compared with its `/translation`
+    pub fn translate_feature(&self, idx: usize) -> Result<JsValue, JsValue> {
+        let feature = self
+            .0
+            .features
+            .get(idx)
+            .ok_or_else(|| Error::new(&format!("No such feature: {}", idx)))?;
+        let res = translate::translate_feature(&self.0, feature).map_err(|e| Error::new(&e))?;
+        Ok(JsValue::from_serde(&res).unwrap())
+    }
+    /// The features whose `/translation` doesn't match their sequence
+    pub fn check_translations(&self) -> Vec<u32> {
+        translate::check_translations(&self.0)
+            .into_iter()
+            .map(|i| i as u32)
+            .collect()
+    }
     pub fn get_seq_slice(&self, start: usize, end: usize) -> String {
         if start > self.0.seq.len() || end > self.0.seq.len() {
             return String::new();
@@ -246,11 +631,104 @@ impl JsSeq {
         seq.topology = Topology::Circular;
         JsSeq(Rc::new(seq))
     }
+    /// Insert `bases` before `pos`, shifting features after it
+    pub fn insert(&self, pos: i32, bases: &str) -> Result<JsSeq, JsValue> {
+        let seq = edit::insert(&self.0, pos as i64, bases.as_bytes()).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    /// Delete `start..end`, which may cross the origin of a circular sequence.
+    /// Features are truncated or dropped.
+    pub fn delete(&self, start: i32, end: i32) -> Result<JsSeq, JsValue> {
+        let seq = edit::delete(&self.0, start as i64, end as i64).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
+    pub fn replace(&self, start: i32, end: i32, bases: &str) -> Result<JsSeq, JsValue> {
+        let seq = edit::replace(&self.0, start as i64, end as i64, bases.as_bytes())
+            .map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(seq)))
+    }
     pub fn clone(&self) -> JsSeq {
         JsSeq(self.0.clone())
     }
 }
 
+/// Settings of type `T`, its defaults if `settings` is undefined or null
+fn parse_settings<T: DeserializeOwned + Default>(settings: &JsValue) -> Result<T, JsValue> {
+    if settings.is_undefined() || settings.is_null() {
+        return Ok(T::default());
+    }
+    settings
+        .into_serde()
+        .map_err(|e| Error::new(&format!("Invalid settings: {}", e)).into())
+}
+
+fn parse_location(location: &str) -> Result<Location, JsValue> {
+    location::parse(location).map_err(|e| Error::new(&format!("Invalid location: {}", e)).into())
+}
+
+fn parse_qualifiers(qualifiers: &JsValue) -> Result<Vec<(QualifierKey, Option<String>)>, JsValue> {
+    if qualifiers.is_undefined() || qualifiers.is_null() {
+        return Ok(Vec::new());
+    }
+    let qualifiers: Vec<(String, Option<String>)> = qualifiers
+        .into_serde()
+        .map_err(|e| Error::new(&format!("Invalid qualifiers: {}", e)))?;
+    feature_edit::make_qualifiers(qualifiers).map_err(|e| Error::new(&e).into())
+}
+
+fn restriction_map(seq: &Seq, settings: &JsValue) -> Result<Vec<restriction::EnzymeSites>, JsValue> {
+    let settings: restriction::RestrictionSettings = parse_settings(settings)?;
+    restriction::map(seq, &settings).map_err(|e| Error::new(&e).into())
+}
+
+/// A list of sequences to be exported as a single multi-record file
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct SeqList {
+    seqs: Vec<Rc<Seq>>,
+}
+
+#[wasm_bindgen]
+impl SeqList {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SeqList {
+        SeqList::default()
+    }
+    pub fn push(&mut self, s: &JsSeq) {
+        self.seqs.push(s.0.clone());
+    }
+    pub fn to_fasta(&self, line_width: usize, header: Option<String>) -> Vec<u8> {
+        let header = header.as_ref().map(String::as_str).unwrap_or(fasta::DEFAULT_HEADER);
+        let mut data = Vec::new();
+        fasta::write_all(
+            &mut data,
+            self.seqs.iter().map(|s| s.as_ref()),
+            line_width,
+            header,
+        )
+        .unwrap();
+        data
+    }
+    pub fn to_json(&self) -> String {
+        json::write_all(self.seqs.iter().map(|s| s.as_ref()))
+    }
+    /// Join the sequences end to end, `settings` is a
+    /// `concat::ConcatSettings`
+    pub fn concatenate(&self, settings: &JsValue) -> Result<JsSeq, JsValue> {
+        let settings: concat::ConcatSettings = parse_settings(settings)?;
+        let seqs: Vec<&Seq> = self.seqs.iter().map(|s| s.as_ref()).collect();
+        let res = concat::concatenate(&seqs, &settings).map_err(|e| Error::new(&e))?;
+        Ok(JsSeq(Rc::new(res)))
+    }
+    pub fn to_gb(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for seq in &self.seqs {
+            seq.write(&mut data).unwrap();
+        }
+        data
+    }
+}
+
 impl<'a> From<&'a Feature> for JsFeature<'a> {
     fn from(f: &'a Feature) -> Self {
         let (start, end) = match f.location.find_bounds() {
@@ -282,3 +760,43 @@ struct JsFeature<'a> {
     start: Option<i64>,
     end: Option<i64>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::archive::test::{gzip, zip, FASTA};
+
+    #[test]
+    fn test_read_fasta() {
+        let seqs = read_fasta(b">one\nACGTX\n").unwrap();
+        assert_eq!(seqs[0].seq, b"ACGTX".to_vec());
+        assert_eq!(
+            read_fasta(">one\nACGT\n>two\nAC\u{e9}GT\n".as_bytes()),
+            Err("Invalid sequence: record \"two\", position 3: invalid character \"\u{e9}\"".into())
+        );
+    }
+
+    #[test]
+    fn test_read_any() {
+        let (format, seqs, errors) = read_any(&gzip(FASTA)).unwrap();
+        assert_eq!(format, detect::Format::Fasta);
+        assert_eq!(seqs.len(), 1);
+        assert!(errors.is_empty());
+
+        let gz = gzip(FASTA);
+        let data = zip(&[("a.fasta.gz", &gz[..]), ("b.txt", &b"not a sequence"[..])]);
+        let (format, seqs, errors) = read_any(&data).unwrap();
+        assert_eq!(format, detect::Format::Zip);
+        assert_eq!(seqs[0].name, Some("a".into()));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].entry, "b.txt");
+        assert_eq!(errors[0].message, "Unknown file format");
+
+        let mut nested = FASTA.to_vec();
+        for _ in 0..MAX_ARCHIVE_DEPTH {
+            nested = gzip(&nested);
+        }
+        assert!(read_any(&nested).is_ok());
+        assert!(read_any(&gzip(&nested)).is_err());
+    }
+}
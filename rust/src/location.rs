@@ -0,0 +1,241 @@
+use gb_io::seq::*;
+
+/// Parse an INSDC location string, e.g. `complement(join(<1..20,30^31))`
+pub fn parse(s: &str) -> Result<Location, String> {
+    let s: Vec<u8> = s.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    let mut p = Parser { s: &s, pos: 0 };
+    let res = p.location()?;
+    if p.pos != s.len() {
+        return Err(p.error());
+    }
+    Ok(res)
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a [u8] {
+        &self.s[self.pos..]
+    }
+    fn error(&self) -> String {
+        format!(
+            "Invalid location at: '{}'",
+            String::from_utf8_lossy(self.rest())
+        )
+    }
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token.as_bytes()) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+    fn number(&mut self) -> Result<i64, String> {
+        let len = self.rest().iter().take_while(|c| c.is_ascii_digit()).count();
+        let res = std::str::from_utf8(&self.rest()[..len])
+            .unwrap()
+            .parse()
+            .map_err(|_| self.error())?;
+        self.pos += len;
+        Ok(res)
+    }
+    fn list(&mut self) -> Result<Vec<Location>, String> {
+        let mut res = vec![self.location()?];
+        while self.eat(",") {
+            res.push(self.location()?);
+        }
+        self.expect(")")?;
+        Ok(res)
+    }
+    fn location(&mut self) -> Result<Location, String> {
+        use Location::*;
+        if self.eat("complement(") {
+            let inner = self.location()?;
+            self.expect(")")?;
+            return Ok(Complement(Box::new(inner)));
+        }
+        if self.eat("join(") {
+            return Ok(Join(self.list()?));
+        }
+        if self.eat("order(") {
+            return Ok(Order(self.list()?));
+        }
+        if self.eat("bond(") {
+            return Ok(Bond(self.list()?));
+        }
+        if self.eat("one-of(") {
+            return Ok(OneOf(self.list()?));
+        }
+        if self.eat("gap(") {
+            let len = self.rest().iter().take_while(|&&c| c != b')').count();
+            let res = std::str::from_utf8(&self.rest()[..len])
+                .unwrap()
+                .parse()
+                .map(GapLength::Known)
+                .unwrap_or(GapLength::Unknown);
+            self.pos += len;
+            self.expect(")")?;
+            return Ok(Gap(res));
+        }
+        if self.rest().first().map(|c| c.is_ascii_alphabetic()) == Some(true) {
+            // reference to another entry, e.g. `J00194.1:100..202`
+            let len = self.rest().iter().take_while(|&&c| c != b':').count();
+            let accession = String::from_utf8_lossy(&self.rest()[..len]).into_owned();
+            self.pos += len;
+            self.expect(":")?;
+            return Ok(External(accession, Some(Box::new(self.location()?))));
+        }
+        let before = self.eat("<");
+        let after_single = self.eat(">");
+        let a = self.number()?;
+        if a < 1 {
+            return Err(self.error());
+        }
+        if self.eat("^") {
+            return Ok(Between(a, self.number()?));
+        }
+        if self.eat("..") {
+            let after = self.eat(">");
+            let b = self.number()?;
+            if b < a {
+                return Err(self.error());
+            }
+            return Ok(Range((a - 1, Before(before)), (b, After(after))));
+        }
+        Ok(Range((a - 1, Before(before)), (a, After(after_single))))
+    }
+}
+
+/// Call `closure(start, end, fwd)` for each `Range` in `p`, in the order
+/// they appear in the location
+// This can't be done in a closure because it's recursive
+pub fn for_each_range(p: &Location, closure: &mut impl FnMut(i64, i64, bool), fwd: bool) {
+    use Location::*;
+    match *p {
+        Complement(ref p) => for_each_range(p, closure, !fwd),
+        Join(ref ps) | OneOf(ref ps) | Bond(ref ps) | Order(ref ps) => {
+            for p in ps {
+                for_each_range(p, closure, fwd);
+            }
+        }
+        Range((a, _), (b, _)) => closure(a, b, fwd),
+        _ => {}
+    }
+}
+
+/// `a..b` as ranges within a sequence of length `len`. Ranges on circular
+/// sequences may extend past the end, these are split at the origin.
+pub fn split_at_origin(a: i64, b: i64, len: i64) -> Vec<Location> {
+    if a >= len {
+        vec![Location::simple_range(a - len, b - len)]
+    } else if b > len {
+        vec![
+            Location::simple_range(a, len),
+            Location::simple_range(0, b - len),
+        ]
+    } else {
+        vec![Location::simple_range(a, b)]
+    }
+}
+
+/// `a..b` on the strand given by `fwd`, joined across the origin if it
+/// extends past the end of the sequence
+pub fn strand_range(a: i64, b: i64, fwd: bool, len: i64) -> Location {
+    let mut ranges = split_at_origin(a, b, len);
+    let l = if ranges.len() == 1 {
+        ranges.pop().unwrap()
+    } else {
+        Location::Join(ranges)
+    };
+    if fwd {
+        l
+    } else {
+        Location::Complement(Box::new(l))
+    }
+}
+
+/// Format a location using INSDC syntax, the inverse of `parse`
+pub fn format(l: &Location) -> String {
+    use Location::*;
+    let list = |name: &str, ls: &[Location]| {
+        let inner: Vec<_> = ls.iter().map(format).collect();
+        format!("{}({})", name, inner.join(","))
+    };
+    match *l {
+        Range((a, Before(before)), (b, After(after))) => {
+            if b == a + 1 && !before {
+                format!("{}{}", if after { ">" } else { "" }, b)
+            } else {
+                format!(
+                    "{}{}..{}{}",
+                    if before { "<" } else { "" },
+                    a + 1,
+                    if after { ">" } else { "" },
+                    b
+                )
+            }
+        }
+        Between(a, b) => format!("{}^{}", a, b),
+        Complement(ref l) => format!("complement({})", format(l)),
+        Join(ref ls) => list("join", ls),
+        Order(ref ls) => list("order", ls),
+        Bond(ref ls) => list("bond", ls),
+        OneOf(ref ls) => list("one-of", ls),
+        External(ref accession, Some(ref l)) => format!("{}:{}", accession, format(l)),
+        External(ref accession, None) => accession.clone(),
+        Gap(GapLength::Known(len)) => format!("gap({})", len),
+        Gap(_) => "gap()".into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for s in &[
+            "1",
+            "1..10",
+            "<1..>10",
+            "5^6",
+            "complement(join(1..10,20..30))",
+            "order(1..2,complement(5..8))",
+            "J00194.1:100..202",
+            "gap(100)",
+        ] {
+            assert_eq!(&format(&parse(s).unwrap()), s);
+        }
+        assert_eq!(
+            parse("join( 1..10,\n 20..30)").unwrap(),
+            Location::Join(vec![
+                Location::simple_range(0, 10),
+                Location::simple_range(19, 30)
+            ])
+        );
+        assert!(parse("10..5").is_err());
+        assert!(parse("join(1..10").is_err());
+    }
+
+    #[test]
+    fn test_strand_range() {
+        assert_eq!(format(&strand_range(2, 5, true, 10)), "3..5");
+        assert_eq!(format(&strand_range(8, 13, true, 10)), "join(9..10,1..3)");
+        assert_eq!(
+            format(&strand_range(8, 13, false, 10)),
+            "complement(join(9..10,1..3))"
+        );
+        assert_eq!(format(&strand_range(12, 13, true, 10)), "3");
+    }
+}
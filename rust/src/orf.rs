@@ -0,0 +1,248 @@
+//! Open reading frames: a start codon followed by whole codons up to and
+//! including a stop codon, starting at the first start after the previous
+//! stop in the same frame. ORFs without a stop are not reported.
+use crate::genetic_code::{self, GeneticCode};
+use crate::location::strand_range;
+use bio::alphabets::dna;
+use gb_io::seq::*;
+use std::collections::HashSet;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrfSettings {
+    /// in amino acids, not counting the stop
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    /// also start at the table's alternative start codons, not only ATG
+    #[serde(default)]
+    pub alternative_starts: bool,
+    #[serde(default = "default_table")]
+    pub table: u8,
+    #[serde(default = "default_both_strands")]
+    pub both_strands: bool,
+}
+
+fn default_min_length() -> usize {
+    75
+}
+
+fn default_table() -> u8 {
+    genetic_code::DEFAULT_TABLE
+}
+
+fn default_both_strands() -> bool {
+    true
+}
+
+impl Default for OrfSettings {
+    fn default() -> OrfSettings {
+        OrfSettings {
+            min_length: default_min_length(),
+            alternative_starts: false,
+            table: default_table(),
+            both_strands: default_both_strands(),
+        }
+    }
+}
+
+/// `end` includes the stop codon, and may be past the end of a circular
+/// sequence
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Orf {
+    pub start: i64,
+    pub end: i64,
+    pub fwd: bool,
+    /// without the stop
+    pub protein: String,
+}
+
+/// ORFs on one strand as `(start, end)`, `end` being at most `len` past
+/// `start`
+fn scan(
+    bases: &[u8],
+    circular: bool,
+    code: &GeneticCode,
+    alternative_starts: bool,
+) -> Vec<(usize, usize)> {
+    let len = bases.len();
+    let is_start = |codon: &[u8]| {
+        if alternative_starts {
+            code.is_start(codon)
+        } else {
+            codon.eq_ignore_ascii_case(b"ATG")
+        }
+    };
+    // reading round a circular sequence returns to the same frame after
+    // `cycle` bases. Go round twice, so every position is seen after a stop
+    // (ORFs are only reported after one), plus enough for an ORF to end.
+    let cycle = if len % 3 == 0 { len } else { 3 * len };
+    let scan_len = if circular { 2 * cycle + len } else { len };
+    let codon_at =
+        |i: usize| -> [u8; 3] { [bases[i % len], bases[(i + 1) % len], bases[(i + 2) % len]] };
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    for frame in 0..3 {
+        // on circular sequences the start of the scan isn't a boundary
+        let mut after_stop = !circular;
+        let mut start = None;
+        let mut i = frame;
+        while i + 3 <= scan_len {
+            let codon = codon_at(i);
+            if code.is_stop(&codon) {
+                if let Some(s) = start {
+                    if i + 3 - s <= len && seen.insert((s % len, i + 3 - s)) {
+                        res.push((s % len, s % len + i + 3 - s));
+                    }
+                }
+                start = None;
+                after_stop = true;
+            } else if start.is_none() && after_stop && is_start(&codon) {
+                start = Some(i);
+            }
+            i += 3;
+        }
+    }
+    res
+}
+
+pub fn find(seq: &Seq, settings: &OrfSettings) -> Result<Vec<Orf>, String> {
+    let code = genetic_code::get(settings.table)?;
+    let len = seq.seq.len();
+    if len < 3 {
+        return Ok(Vec::new());
+    }
+    let circular = seq.is_circular();
+    let rc = dna::revcomp(&seq.seq);
+    let mut strands = vec![(true, &seq.seq)];
+    if settings.both_strands {
+        strands.push((false, &rc));
+    }
+    let mut res = Vec::new();
+    for (fwd, bases) in strands {
+        for (start, end) in scan(bases, circular, code, settings.alternative_starts) {
+            let n_codons = (end - start) / 3;
+            if n_codons - 1 < settings.min_length {
+                continue;
+            }
+            let nt: Vec<u8> = (start..end - 3).map(|i| bases[i % len]).collect();
+            let protein = String::from_utf8(code.translate(&nt, true)).unwrap();
+            let (start, end) = if fwd {
+                (start as i64, end as i64)
+            } else {
+                // back to forward strand coordinates
+                let s = (len as i64 - end as i64).rem_euclid(len as i64);
+                (s, s + (end - start) as i64)
+            };
+            res.push(Orf {
+                start,
+                end,
+                fwd,
+                protein,
+            });
+        }
+    }
+    res.sort_by_key(|o| (o.start, o.end, !o.fwd));
+    Ok(res)
+}
+
+/// `seq` with a `CDS` feature for each ORF
+pub fn annotate(seq: &Seq, orfs: &[Orf], table: u8) -> Seq {
+    let mut res = seq.clone();
+    for (i, orf) in orfs.iter().enumerate() {
+        let location = strand_range(orf.start, orf.end, orf.fwd, seq.len());
+        res.features.push(Feature {
+            kind: feature_kind!("CDS"),
+            location,
+            qualifiers: vec![
+                (qualifier_key!("label"), Some(format!("ORF{}", i + 1))),
+                (qualifier_key!("codon_start"), Some("1".into())),
+                (qualifier_key!("transl_table"), Some(table.to_string())),
+                (qualifier_key!("translation"), Some(orf.protein.clone())),
+            ],
+        });
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn seq(bases: &[u8], circular: bool) -> Seq {
+        Seq {
+            seq: bases.to_vec(),
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            ..Seq::empty()
+        }
+    }
+
+    fn settings(min_length: usize) -> OrfSettings {
+        OrfSettings {
+            min_length,
+            ..OrfSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_linear() {
+        // ATG AAA ATG GCC TAA, the second ATG being part of the same ORF,
+        // and GTG CCC GCC TAA which is only an ORF with alternative starts
+        let s = seq(b"cATGAAAATGGCCTAAcGTGCCCGCCTAAc", false);
+        let orfs = find(&s, &settings(3)).unwrap();
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 1,
+                end: 16,
+                fwd: true,
+                protein: "MKMA".into()
+            }]
+        );
+        assert!(find(&s, &settings(5)).unwrap().is_empty());
+        let alt = OrfSettings {
+            alternative_starts: true,
+            table: 11,
+            ..settings(3)
+        };
+        let orfs = find(&s, &alt).unwrap();
+        assert_eq!(orfs.len(), 2);
+        assert_eq!((orfs[1].start, orfs[1].end), (17, 29));
+        assert_eq!(orfs[1].protein, "MPA");
+        let bad = OrfSettings {
+            table: 8,
+            ..settings(3)
+        };
+        assert!(find(&s, &bad).is_err());
+    }
+
+    #[test]
+    fn test_circular() {
+        // ATG AAA CCC TAG across the origin on the reverse strand
+        let rc = dna::revcomp(&b"ATGAAACCCTAG"[..]);
+        let mut bases = rc[4..].to_vec();
+        bases.extend_from_slice(b"ggggg");
+        bases.extend_from_slice(&rc[..4]);
+        let s = seq(&bases, true);
+        let orfs = find(&s, &settings(3)).unwrap();
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 13,
+                end: 25,
+                fwd: false,
+                protein: "MKP".into()
+            }]
+        );
+        // not on a linear sequence
+        assert!(find(&seq(&bases, false), &settings(3)).unwrap().is_empty());
+        let annotated = annotate(&s, &orfs, 1);
+        assert_eq!(
+            crate::location::format(&annotated.features[0].location),
+            "complement(join(14..17,1..8))"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Import and export of primer lists as CSV/TSV tables, e.g. the order
+//! spreadsheets from oligo suppliers.
+use crate::fasta::is_iupac;
+use crate::js_pcr::JsPrimerRef;
+use js_sys::Error;
+use wasm_bindgen::prelude::*;
+
+/// Which columns to read, by (case-insensitive) header name. Columns which
+/// aren't given are guessed from the header.
+#[derive(Deserialize, Default, Debug)]
+pub struct ColumnMapping {
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub seq: Option<String>,
+    #[serde(default)]
+    pub desc: Option<String>,
+}
+
+const NAME_COLUMNS: &[&str] = &["name", "primer", "primer name", "oligo", "oligo name", "id"];
+const SEQ_COLUMNS: &[&str] = &[
+    "seq",
+    "sequence",
+    "sequence (5'-3')",
+    "sequence 5'-3'",
+    "5'-3'",
+    "bases",
+];
+const DESC_COLUMNS: &[&str] = &[
+    "desc",
+    "description",
+    "notes",
+    "note",
+    "comment",
+    "comments",
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RowError {
+    /// 1-based line number
+    pub line: u64,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PrimerTable {
+    pub primers: Vec<JsPrimerRef>,
+    pub errors: Vec<RowError>,
+}
+
+/// Remove whitespace, `5'-`/`-3'` markers and modifications written as
+/// `/5Phos/` (IDT style) or `[Phos]`, then check that only IUPAC bases are
+/// left
+pub fn clean_seq(raw: &str) -> Result<String, String> {
+    let mut res = String::new();
+    let mut in_mod = None;
+    let mut s = raw.trim();
+    for prefix in &["5'-", "5'", "5-"] {
+        if s.starts_with(prefix) {
+            s = &s[prefix.len()..];
+            break;
+        }
+    }
+    for suffix in &["-3'", "3'", "-3"] {
+        if s.ends_with(suffix) {
+            s = &s[..s.len() - suffix.len()];
+            break;
+        }
+    }
+    for c in s.chars() {
+        match (in_mod, c) {
+            (None, '/') => in_mod = Some('/'),
+            (None, '[') => in_mod = Some(']'),
+            (Some(end), c) if c == end => in_mod = None,
+            (Some(_), _) => {}
+            (None, c) if c.is_whitespace() => {}
+            (None, c) => res.push(c),
+        }
+    }
+    if in_mod.is_some() {
+        return Err("Unterminated modification".into());
+    }
+    if res.is_empty() {
+        return Err("Empty sequence".into());
+    }
+    if let Some(c) = res.bytes().find(|&c| !is_iupac(c)) {
+        return Err(format!("Invalid base: {:?}", c as char));
+    }
+    Ok(res)
+}
+
+fn guess_delimiter(data: &[u8]) -> u8 {
+    let first_line = data.split(|&c| c == b'\n').next().unwrap_or_default();
+    [b'\t', b';', b',']
+        .iter()
+        .cloned()
+        .max_by_key(|&d| first_line.iter().filter(|&&c| c == d).count())
+        .filter(|&d| first_line.contains(&d))
+        .unwrap_or(b',')
+}
+
+fn find_column(
+    headers: &[String],
+    requested: &Option<String>,
+    candidates: &[&str],
+) -> Result<Option<usize>, String> {
+    match requested {
+        Some(name) => headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name.trim()))
+            .map(Some)
+            .ok_or_else(|| format!("No column named {:?}", name)),
+        None => Ok(candidates
+            .iter()
+            .filter_map(|c| headers.iter().position(|h| h.eq_ignore_ascii_case(c)))
+            .next()),
+    }
+}
+
+pub fn parse(data: &[u8], mapping: &ColumnMapping) -> Result<PrimerTable, String> {
+    // skip a UTF-8 byte order mark, spreadsheets like adding them
+    let data = if data.starts_with(b"\xef\xbb\xbf") {
+        &data[3..]
+    } else {
+        data
+    };
+    let delimiter = match mapping.delimiter {
+        Some(d) if d.is_ascii() => d as u8,
+        Some(d) => return Err(format!("Invalid delimiter: {:?}", d)),
+        None => guess_delimiter(data),
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(data);
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Reading header failed: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let name_col = find_column(&headers, &mapping.name, NAME_COLUMNS)?
+        .ok_or_else(|| String::from("Couldn't find a name column"))?;
+    let seq_col = find_column(&headers, &mapping.seq, SEQ_COLUMNS)?
+        .ok_or_else(|| String::from("Couldn't find a sequence column"))?;
+    let desc_col = find_column(&headers, &mapping.desc, DESC_COLUMNS)?;
+    let mut primers = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(RowError {
+                    line: e.position().map(|p| p.line()).unwrap_or_default(),
+                    message: format!("{}", e),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let field = |i: usize| record.get(i).unwrap_or_default().trim();
+        let name = field(name_col);
+        if name.is_empty() {
+            errors.push(RowError {
+                line,
+                message: "Missing name".into(),
+            });
+            continue;
+        }
+        match clean_seq(field(seq_col)) {
+            Ok(seq) => primers.push(JsPrimerRef {
+                name: name.into(),
+                seq,
+                desc: desc_col.map(field).unwrap_or_default().into(),
+            }),
+            Err(message) => errors.push(RowError {
+                line,
+                message: format!("{}: {}", name, message),
+            }),
+        }
+    }
+    Ok(PrimerTable { primers, errors })
+}
+
+pub fn write(primers: &[JsPrimerRef], delimiter: u8) -> Result<Vec<u8>, String> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+    let err = |e: csv::Error| format!("Writing failed: {}", e);
+    writer.write_record(&["name", "seq", "desc"]).map_err(err)?;
+    for p in primers {
+        writer
+            .write_record(&[&p.name, &p.seq, &p.desc])
+            .map_err(err)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| format!("Writing failed: {}", e))
+}
+
+/// Parse a primer table, `mapping` is an optional `ColumnMapping`. Rows
+/// which can't be read are reported in `errors` rather than failing the
+/// whole table.
+#[wasm_bindgen]
+pub fn parse_primer_table(data: &[u8], mapping: &JsValue) -> Result<JsValue, JsValue> {
+    let mapping: ColumnMapping = crate::parse_settings(mapping)?;
+    let res = parse(data, &mapping).map_err(|e| Error::new(&e))?;
+    Ok(JsValue::from_serde(&res).unwrap())
+}
+
+#[wasm_bindgen]
+pub fn write_primer_table(primers: Box<[JsValue]>, tsv: bool) -> Result<Vec<u8>, JsValue> {
+    let primers: Vec<JsPrimerRef> = primers
+        .iter()
+        .map(|p| p.into_serde())
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::new(&format!("Invalid primer: {}", e)))?;
+    write(&primers, if tsv { b'\t' } else { b',' }).map_err(|e| Error::new(&e).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean_seq() {
+        assert_eq!(clean_seq(" 5'-/5Phos/ACG TNN-3' ").unwrap(), "ACGTNN");
+        assert_eq!(clean_seq("[Btn]acgtRY").unwrap(), "acgtRY");
+        assert!(clean_seq("ACGTX").is_err());
+        assert!(clean_seq("/5Phos").is_err());
+        assert!(clean_seq("  ").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = "Oligo Name\tSequence 5'-3'\tScale\tNotes\n\
+                    p1\t/56-FAM/acgtacgt\t25nm\tfirst\n\
+                    \n\
+                    p2\tACGTZ\t25nm\t\n\
+                    \tACGT\t25nm\t\n\
+                    p3\tgg cc\n";
+        let res = parse(data.as_bytes(), &ColumnMapping::default()).unwrap();
+        assert_eq!(res.primers.len(), 2);
+        assert_eq!(res.primers[0].seq, "acgtacgt");
+        assert_eq!(res.primers[0].desc, "first");
+        assert_eq!(res.primers[1].name, "p3");
+        assert_eq!(res.primers[1].seq, "ggcc");
+        assert_eq!(
+            res.errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        let mapping = ColumnMapping {
+            seq: Some("scale".into()),
+            ..ColumnMapping::default()
+        };
+        assert!(parse(data.as_bytes(), &mapping).unwrap().primers.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let primers = vec![JsPrimerRef {
+            name: "fwd, 1".into(),
+            seq: "acgt".into(),
+            desc: "".into(),
+        }];
+        let data = write(&primers, b',').unwrap();
+        let res = parse(&data, &ColumnMapping::default()).unwrap();
+        assert_eq!(res.primers[0].name, "fwd, 1");
+        assert!(res.errors.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+//! Project bundles: a whole workspace (sequences, primers and saved PCR and
+//! assembly setups) in one file, so it can be shared or restored.
+//!
+//! The layout is `PROJECT_MAGIC`, a bincode encoded `ProjectVersion` tag and
+//! then a bincode encoded `ProjectFile`. Sequences are stored in the binary
+//! sequence format, so they carry their own version and are decoded with
+//! `bin_format::decode`.
+use crate::assembly::JsAssemblySettings;
+use crate::bin_format;
+use crate::js_pcr::{JsPrimerRef, PcrSettings};
+use crate::JsSeq;
+use bincode::{deserialize_from, serialize_into};
+use gb_io::seq::Seq;
+use js_sys::Error;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+pub const PROJECT_MAGIC: &[u8] = b"CLNPROJ\x00";
+
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct ProjectVersion(pub u32);
+
+// bump this (and keep decoding the old version) if `ProjectFile` changes
+pub const PROJECT_FORMAT_VERSION: ProjectVersion = ProjectVersion(1);
+
+/// A saved PCR: which sequence to use as the template, the primers used are
+/// the project's primer list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct PcrSetup {
+    seq: usize,
+    settings: PcrSettings,
+}
+
+/// A saved assembly: which sequences to assemble, in order
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct AssemblySetup {
+    seqs: Vec<usize>,
+    settings: JsAssemblySettings,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    name: Option<String>,
+    seqs: Vec<Vec<u8>>,
+    primers: Vec<JsPrimerRef>,
+    pcr: Vec<PcrSetup>,
+    assembly: Vec<AssemblySetup>,
+}
+
+pub fn is_project(data: &[u8]) -> bool {
+    data.starts_with(PROJECT_MAGIC)
+}
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Project {
+    name: Option<String>,
+    seqs: Vec<Rc<Seq>>,
+    primers: Vec<JsPrimerRef>,
+    pcr: Vec<PcrSetup>,
+    assembly: Vec<AssemblySetup>,
+}
+
+impl Project {
+    pub fn seqs(&self) -> &[Rc<Seq>] {
+        &self.seqs
+    }
+
+    fn check_seq(&self, idx: usize) -> Result<(), String> {
+        if idx < self.seqs.len() {
+            Ok(())
+        } else {
+            Err(format!("No such sequence: {}", idx))
+        }
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let file = ProjectFile {
+            name: self.name.clone(),
+            seqs: self
+                .seqs
+                .iter()
+                .map(|s| bin_format::encode(s))
+                .collect::<Result<_, _>>()?,
+            primers: self.primers.clone(),
+            pcr: self.pcr.clone(),
+            assembly: self.assembly.clone(),
+        };
+        let mut res = PROJECT_MAGIC.to_vec();
+        let mut buf = &mut res;
+        serialize_into(&mut buf, &PROJECT_FORMAT_VERSION).expect("Writing header failed");
+        serialize_into(&mut buf, &file).map_err(|e| format!("Couldn't encode: {}", e))?;
+        Ok(res)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Project, String> {
+        if !is_project(data) {
+            return Err("Not a project file".into());
+        }
+        let mut data = &data[PROJECT_MAGIC.len()..];
+        let ver: ProjectVersion =
+            deserialize_from(&mut data).map_err(|e| format!("Decoding header failed: {}", e))?;
+        let file: ProjectFile = match ver.0 {
+            1 => deserialize_from(&mut data).map_err(|e| format!("Decoding failed: {}", e))?,
+            v => return Err(format!("Can't decode version {} of project format", v)),
+        };
+        let seqs = file
+            .seqs
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                bin_format::decode(s)
+                    .map(Rc::new)
+                    .map_err(|e| format!("Sequence {}: {}", i, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let res = Project {
+            name: file.name,
+            seqs,
+            primers: file.primers,
+            pcr: file.pcr,
+            assembly: file.assembly,
+        };
+        for p in &res.pcr {
+            res.check_seq(p.seq)?;
+        }
+        for a in &res.assembly {
+            for &s in &a.seqs {
+                res.check_seq(s)?;
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[wasm_bindgen]
+impl Project {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Project {
+        Project::default()
+    }
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+    pub fn get_name(&self) -> Option<String> {
+        self.name.clone()
+    }
+    /// Add a sequence, returning its index
+    pub fn push_seq(&mut self, s: &JsSeq) -> usize {
+        self.seqs.push(s.0.clone());
+        self.seqs.len() - 1
+    }
+    pub fn get_seqs(&self) -> Box<[JsValue]> {
+        self.seqs
+            .iter()
+            .map(|s| JsValue::from(JsSeq(s.clone())))
+            .collect::<Vec<_>>()
+            .into_boxed_slice()
+    }
+    pub fn set_primers(&mut self, primers: Box<[JsValue]>) -> Result<(), JsValue> {
+        self.primers = primers
+            .iter()
+            .map(|p| p.into_serde())
+            .collect::<Result<_, _>>()
+            .map_err(|e| Error::new(&format!("Invalid primer: {}", e)))?;
+        Ok(())
+    }
+    pub fn get_primers(&self) -> JsValue {
+        JsValue::from_serde(&self.primers).unwrap()
+    }
+    pub fn push_pcr(&mut self, seq: usize, settings: &JsValue) -> Result<(), JsValue> {
+        self.check_seq(seq).map_err(|e| Error::new(&e))?;
+        let settings = settings
+            .into_serde()
+            .map_err(|e| Error::new(&format!("Invalid settings: {}", e)))?;
+        self.pcr.push(PcrSetup { seq, settings });
+        Ok(())
+    }
+    pub fn get_pcr(&self) -> JsValue {
+        JsValue::from_serde(&self.pcr).unwrap()
+    }
+    pub fn push_assembly(&mut self, seqs: Vec<u32>, settings: &JsValue) -> Result<(), JsValue> {
+        let seqs: Vec<usize> = seqs.into_iter().map(|s| s as usize).collect();
+        for &s in &seqs {
+            self.check_seq(s).map_err(|e| Error::new(&e))?;
+        }
+        let settings = settings
+            .into_serde()
+            .map_err(|e| Error::new(&format!("Invalid settings: {}", e)))?;
+        self.assembly.push(AssemblySetup { seqs, settings });
+        Ok(())
+    }
+    pub fn get_assembly(&self) -> JsValue {
+        JsValue::from_serde(&self.assembly).unwrap()
+    }
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsValue> {
+        self.encode().map_err(|e| Error::new(&e).into())
+    }
+}
+
+#[wasm_bindgen]
+pub fn parse_project(data: &[u8]) -> Result<Project, JsValue> {
+    Project::decode(data).map_err(|e| Error::new(&e).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let seq = Seq {
+            name: Some("template".into()),
+            seq: b"acgtacgtNNacgt".to_vec(),
+            ..Seq::empty()
+        };
+        let project = Project {
+            name: Some("test".into()),
+            seqs: vec![Rc::new(seq.clone()), Rc::new(seq.clone())],
+            primers: vec![JsPrimerRef {
+                name: "fwd".into(),
+                seq: "acgtacg".into(),
+                desc: "".into(),
+            }],
+            pcr: vec![PcrSetup {
+                seq: 1,
+                settings: PcrSettings {
+                    min_fp: 15,
+                    min_len: 0,
+                    max_len: 10000,
+                },
+            }],
+            assembly: vec![AssemblySetup {
+                seqs: vec![0, 1],
+                settings: JsAssemblySettings { limit: 20 },
+            }],
+        };
+        let data = project.encode().unwrap();
+        assert!(is_project(&data));
+        let decoded = Project::decode(&data).unwrap();
+        assert_eq!(decoded.name, Some("test".into()));
+        assert_eq!(decoded.seqs.len(), 2);
+        assert_eq!(*decoded.seqs[1], seq);
+        assert_eq!(decoded.primers[0].name, "fwd");
+        assert_eq!(decoded.pcr[0].seq, 1);
+        assert_eq!(decoded.assembly[0].seqs, vec![0, 1]);
+        assert_eq!(decoded.assembly[0].settings.limit, 20);
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        let project = Project {
+            pcr: vec![PcrSetup {
+                seq: 0,
+                settings: PcrSettings {
+                    min_fp: 15,
+                    min_len: 0,
+                    max_len: 10000,
+                },
+            }],
+            ..Project::default()
+        };
+        assert!(Project::decode(&project.encode().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Restriction mapping with the enzymes in `enzymes`. Sites are found on
+//! both strands, across the origin of circular sequences, and each site is
+//! reported with the positions its enzyme cuts at.
+use crate::enzymes::{self, Enzyme, ENZYMES};
+use crate::genetic_code::base_bits;
+use gb_io::seq::*;
+use std::str;
+use wasm_bindgen::prelude::*;
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RestrictionSettings {
+    /// names of the enzymes to map, all of them if not given
+    #[serde(default)]
+    pub enzymes: Option<Vec<String>>,
+}
+
+/// `end` may be past the end of a circular sequence, the cuts are always
+/// within it. `cut` is in the top strand, `cut_bottom` in the bottom one.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Site {
+    pub start: i64,
+    pub end: i64,
+    /// whether the recognition sequence is read on the top strand
+    pub fwd: bool,
+    pub cut: i64,
+    pub cut_bottom: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EnzymeSites {
+    pub name: &'static str,
+    pub site: &'static str,
+    pub isoschizomers: Vec<&'static str>,
+    pub sites: Vec<Site>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq)]
+pub struct DigestSummary {
+    pub unique: Vec<&'static str>,
+    pub double: Vec<&'static str>,
+    pub noncutters: Vec<&'static str>,
+}
+
+fn selected(settings: &RestrictionSettings) -> Result<Vec<&'static Enzyme>, String> {
+    match settings.enzymes {
+        None => Ok(ENZYMES.iter().collect()),
+        Some(ref names) => names
+            .iter()
+            .map(|name| {
+                ENZYMES
+                    .iter()
+                    .find(|e| e.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("Unknown enzyme: {}", name))
+            })
+            .collect(),
+    }
+}
+
+/// The complement of a set of bases as given by `base_bits`, which swaps
+/// T and A, and C and G
+fn complement_bits(bits: u8) -> u8 {
+    (bits << 2 | bits >> 2) & 0b1111
+}
+
+/// The sites of one enzyme in a sequence given as `base_bits` of each base,
+/// whose ambiguity codes never match
+fn find_sites(enzyme: &Enzyme, seq_bits: &[u8], circular: bool) -> Vec<Site> {
+    let len = seq_bits.len();
+    let site_len = enzyme.site.len();
+    if len < site_len || site_len == 0 {
+        return Vec::new();
+    }
+    let fwd_bits: Vec<u8> = enzyme.site.iter().map(|&c| base_bits(c)).collect();
+    let rev_bits: Vec<u8> = fwd_bits.iter().rev().map(|&b| complement_bits(b)).collect();
+    let palindromic = fwd_bits == rev_bits;
+    let matches = |pattern: &[u8], p: usize| {
+        pattern.iter().enumerate().all(|(i, &s)| {
+            let b = seq_bits[(p + i) % len];
+            b.count_ones() == 1 && b & s != 0
+        })
+    };
+    let (len, site_len) = (len as i64, site_len as i64);
+    let n_starts = if circular { len } else { len - site_len + 1 };
+    let mut res = Vec::new();
+    for p in 0..n_starts {
+        let mut add = |fwd: bool, cut: i64, cut_bottom: i64| {
+            let in_bounds = |c: i64| circular || (c >= 0 && c <= len);
+            // a linear sequence isn't cut if a cut would be past its end
+            if in_bounds(cut) && in_bounds(cut_bottom) {
+                res.push(Site {
+                    start: p,
+                    end: p + site_len,
+                    fwd,
+                    cut: cut.rem_euclid(len),
+                    cut_bottom: cut_bottom.rem_euclid(len),
+                });
+            }
+        };
+        if matches(&fwd_bits, p as usize) {
+            add(true, p + enzyme.cut, p + enzyme.cut_bottom);
+        }
+        if !palindromic && matches(&rev_bits, p as usize) {
+            add(
+                false,
+                p + site_len - enzyme.cut_bottom,
+                p + site_len - enzyme.cut,
+            );
+        }
+    }
+    res
+}
+
+pub fn map(seq: &Seq, settings: &RestrictionSettings) -> Result<Vec<EnzymeSites>, String> {
+    let circular = seq.is_circular();
+    let seq_bits: Vec<u8> = seq.seq.iter().map(|&c| base_bits(c)).collect();
+    Ok(selected(settings)?
+        .into_iter()
+        .map(|e| EnzymeSites {
+            name: e.name,
+            site: str::from_utf8(e.site).unwrap(),
+            isoschizomers: enzymes::isoschizomers(e),
+            sites: find_sites(e, &seq_bits, circular),
+        })
+        .collect())
+}
+
+pub fn summary(sites: &[EnzymeSites]) -> DigestSummary {
+    let mut res = DigestSummary::default();
+    for e in sites {
+        match e.sites.len() {
+            0 => res.noncutters.push(e.name),
+            1 => res.unique.push(e.name),
+            2 => res.double.push(e.name),
+            _ => {}
+        }
+    }
+    res
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnzymeEntry {
+    name: &'static str,
+    site: &'static str,
+    cut: i64,
+    cut_bottom: i64,
+    isoschizomers: Vec<&'static str>,
+}
+
+/// The enzymes `JsSeq::restriction_sites` can map
+#[wasm_bindgen]
+pub fn get_enzymes() -> JsValue {
+    let entries: Vec<_> = ENZYMES
+        .iter()
+        .map(|e| EnzymeEntry {
+            name: e.name,
+            site: str::from_utf8(e.site).unwrap(),
+            cut: e.cut,
+            cut_bottom: e.cut_bottom,
+            isoschizomers: enzymes::isoschizomers(e),
+        })
+        .collect();
+    JsValue::from_serde(&entries).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn seq(bases: &[u8], circular: bool) -> Seq {
+        Seq {
+            seq: bases.to_vec(),
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            ..Seq::empty()
+        }
+    }
+
+    fn settings(names: &[&str]) -> RestrictionSettings {
+        RestrictionSettings {
+            enzymes: Some(names.iter().map(|n| n.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        // EcoRI across the origin, BsaI on the reverse strand cutting across
+        // it too, and a degenerate AvaI site
+        let s = seq(b"ATTCGAGACCaaaaaaCTCGGGttttttatatGA", true);
+        let res = map(&s, &settings(&["EcoRI", "bsai", "AvaI", "NotI"])).unwrap();
+        assert_eq!(
+            res[0].sites,
+            vec![Site {
+                start: 32,
+                end: 38,
+                fwd: true,
+                cut: 33,
+                cut_bottom: 3,
+            }]
+        );
+        // the cuts are 1 and 5 bases before the start of GAGACC
+        assert_eq!(
+            res[1].sites,
+            vec![Site {
+                start: 4,
+                end: 10,
+                fwd: false,
+                cut: 33,
+                cut_bottom: 3,
+            }]
+        );
+        assert_eq!(res[2].sites.len(), 1);
+        assert_eq!(res[2].sites[0].cut, 17);
+        assert_eq!(
+            summary(&res),
+            DigestSummary {
+                unique: vec!["EcoRI", "BsaI", "AvaI"],
+                double: Vec::new(),
+                noncutters: vec!["NotI"],
+            }
+        );
+        // no EcoRI site and BsaI can't cut the linear sequence
+        let res = map(&seq(&s.seq, false), &settings(&["EcoRI", "BsaI"])).unwrap();
+        assert!(res.iter().all(|e| e.sites.is_empty()));
+        assert!(map(&s, &settings(&["EcoRJ"])).is_err());
+    }
+
+    #[test]
+    fn test_complement_bits() {
+        assert_eq!(complement_bits(base_bits(b'A')), base_bits(b'T'));
+        assert_eq!(complement_bits(base_bits(b'C')), base_bits(b'G'));
+        assert_eq!(complement_bits(base_bits(b'R')), base_bits(b'Y'));
+        assert_eq!(complement_bits(base_bits(b'M')), base_bits(b'K'));
+        assert_eq!(complement_bits(base_bits(b'S')), base_bits(b'S'));
+    }
+}
@@ -0,0 +1,467 @@
+//! SBOL3 (RDF/XML) export of assembly products. The product is described as
+//! a `Component` made of `SubComponent`s, one for each fragment, with the
+//! homologous overlaps between them as `SequenceFeature`s.
+use assembly::{Match, MatchIdx, Node, Path};
+use bio::alphabets::dna;
+use gb_io::seq::Seq;
+use std::fmt::Write;
+
+const SBOL: &str = "http://sbols.org/v3#";
+const DNA: &str = "https://identifiers.org/SBO:0000251";
+const ENGINEERED_REGION: &str = "https://identifiers.org/SO:0000804";
+const CONSERVED_REGION: &str = "https://identifiers.org/SO:0000330";
+const LINEAR: &str = "https://identifiers.org/SO:0000987";
+const CIRCULAR: &str = "https://identifiers.org/SO:0000988";
+const INLINE: &str = "https://identifiers.org/SO:0001030";
+const REVERSE_COMPLEMENT: &str = "https://identifiers.org/SO:0001031";
+const IUPAC_DNA: &str = "https://identifiers.org/edam:format_1207";
+
+/// One fragment of a product: the part of the (possibly reverse
+/// complemented) fragment which is used, and where it ends up
+struct Part<'a> {
+    seq: &'a Seq,
+    fwd: bool,
+    /// range used in the orientation of the product
+    start: usize,
+    end: usize,
+    /// start in the product
+    pos: usize,
+    /// length of the overlap with the previous fragment
+    overlap: usize,
+}
+
+impl<'a> Part<'a> {
+    /// Start of the range used, on the fragment's own forward strand
+    fn source_start(&self) -> usize {
+        if self.fwd {
+            self.start
+        } else {
+            self.seq.seq.len() - self.end
+        }
+    }
+}
+
+fn parts<'a>(path: &Path, seqs: &[&'a Seq]) -> (Vec<Part<'a>>, bool) {
+    let circular = (path[0].1).2 != 0;
+    let mut res: Vec<Part<'a>> = Vec::new();
+    for (i, &Node(idx, Match(_, to, len))) in path.iter().enumerate() {
+        let seq = seqs[idx.index()];
+        let end = match path.get(i + 1) {
+            Some(&Node(_, Match(from, _, len))) => from + len,
+            None if circular => (path[0].1).0 + (path[0].1).2,
+            None => seq.seq.len() as u32,
+        };
+        let start = if i == 0 && !circular { 0 } else { to };
+        let pos = match res.last() {
+            Some(prev) => prev.pos + prev.end - prev.start - len as usize,
+            None => 0,
+        };
+        res.push(Part {
+            seq,
+            fwd: match idx {
+                MatchIdx::Idx(_) => true,
+                MatchIdx::IdxRc(_) => false,
+            },
+            start: start as usize,
+            end: (end as usize).min(seq.seq.len()),
+            pos,
+            overlap: len as usize,
+        });
+    }
+    (res, circular)
+}
+
+/// Make a valid SBOL `displayId`
+fn display_id(name: &str) -> String {
+    let mut res: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !res.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        res.insert(0, '_');
+    }
+    res
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn write_sequence(out: &mut String, uri: &str, seq: &[u8]) {
+    let id = uri.rsplit('/').next().unwrap_or_default();
+    writeln!(out, "  <sbol:Sequence rdf:about=\"{}\">", uri).unwrap();
+    writeln!(out, "    <sbol:displayId>{}</sbol:displayId>", id).unwrap();
+    writeln!(
+        out,
+        "    <sbol:elements>{}</sbol:elements>",
+        escape(&String::from_utf8_lossy(seq).to_ascii_lowercase())
+    )
+    .unwrap();
+    writeln!(out, "    <sbol:encoding rdf:resource=\"{}\"/>", IUPAC_DNA).unwrap();
+    writeln!(
+        out,
+        "    <sbol:hasNamespace rdf:resource=\"{}\"/>",
+        namespace_of(uri)
+    )
+    .unwrap();
+    writeln!(out, "  </sbol:Sequence>").unwrap();
+}
+
+fn namespace_of(uri: &str) -> &str {
+    uri.rsplitn(2, '/').nth(1).unwrap_or(uri)
+}
+
+/// Write `Range`s covering `len` bases starting at `start` (0-based), split
+/// in two if the range crosses the origin of a circular sequence
+#[allow(clippy::too_many_arguments)]
+fn write_ranges(
+    out: &mut String,
+    parent: &str,
+    property: &str,
+    counter: &mut usize,
+    seq_uri: &str,
+    start: usize,
+    len: usize,
+    total: usize,
+    orientation: &str,
+) {
+    let mut ranges = vec![(start, (start + len).min(total))];
+    if start + len > total {
+        ranges.push((0, start + len - total));
+    }
+    for (s, e) in ranges {
+        *counter += 1;
+        let id = format!("Range{}", counter);
+        writeln!(out, "      <sbol:{}>", property).unwrap();
+        writeln!(out, "        <sbol:Range rdf:about=\"{}/{}\">", parent, id).unwrap();
+        writeln!(out, "          <sbol:displayId>{}</sbol:displayId>", id).unwrap();
+        writeln!(
+            out,
+            "          <sbol:hasSequence rdf:resource=\"{}\"/>",
+            seq_uri
+        )
+        .unwrap();
+        writeln!(out, "          <sbol:start>{}</sbol:start>", s + 1).unwrap();
+        writeln!(out, "          <sbol:end>{}</sbol:end>", e).unwrap();
+        writeln!(
+            out,
+            "          <sbol:orientation rdf:resource=\"{}\"/>",
+            orientation
+        )
+        .unwrap();
+        writeln!(out, "        </sbol:Range>").unwrap();
+        writeln!(out, "      </sbol:{}>", property).unwrap();
+    }
+}
+
+/// Export the product described by `path` as an SBOL3 document. All URIs
+/// are created under `namespace`.
+pub fn export_product(namespace: &str, name: &str, seqs: &[&Seq], path: &Path) -> String {
+    let namespace = namespace.trim_end_matches('/');
+    let (parts, circular) = parts(path, seqs);
+    let mut product_seq = Vec::new();
+    for (i, p) in parts.iter().enumerate() {
+        let oriented = if p.fwd {
+            p.seq.seq[p.start..p.end].to_vec()
+        } else {
+            dna::revcomp(&p.seq.seq)[p.start..p.end].to_vec()
+        };
+        // the overlap with the previous part is already included
+        let skip = if i == 0 { 0 } else { p.overlap };
+        let mut used = &oriented[skip.min(oriented.len())..];
+        if circular && i == parts.len() - 1 {
+            let wrap = parts[0].overlap.min(used.len());
+            used = &used[..used.len() - wrap];
+        }
+        product_seq.extend_from_slice(used);
+    }
+    let total = product_seq.len();
+
+    let product_id = display_id(name);
+    let product_uri = format!("{}/{}", namespace, product_id);
+    let product_seq_uri = format!("{}_sequence", product_uri);
+    let mut part_ids: Vec<String> = Vec::new();
+    for p in &parts {
+        let id = display_id(&p.seq.name.clone().unwrap_or_else(|| "fragment".into()));
+        let mut unique = id.clone();
+        let mut n = 1;
+        while unique == product_id || part_ids.contains(&unique) {
+            n += 1;
+            unique = format!("{}_{}", id, n);
+        }
+        part_ids.push(unique);
+    }
+    // fragments used more than once are only described once
+    let mut components: Vec<(&Seq, &str)> = Vec::new();
+    for (p, id) in parts.iter().zip(&part_ids) {
+        if !components.iter().any(|&(s, _)| std::ptr::eq(s, p.seq)) {
+            components.push((p.seq, id));
+        }
+    }
+    let component_of = |seq: &Seq| -> String {
+        let &(_, id) = components
+            .iter()
+            .find(|&&(s, _)| std::ptr::eq(s, seq))
+            .unwrap();
+        format!("{}/{}", namespace, id)
+    };
+
+    let mut out = String::new();
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+    writeln!(
+        out,
+        "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:sbol=\"{}\">",
+        SBOL
+    )
+    .unwrap();
+
+    writeln!(out, "  <sbol:Component rdf:about=\"{}\">", product_uri).unwrap();
+    writeln!(out, "    <sbol:displayId>{}</sbol:displayId>", product_id).unwrap();
+    writeln!(out, "    <sbol:name>{}</sbol:name>", escape(name)).unwrap();
+    writeln!(
+        out,
+        "    <sbol:hasNamespace rdf:resource=\"{}\"/>",
+        namespace
+    )
+    .unwrap();
+    writeln!(out, "    <sbol:type rdf:resource=\"{}\"/>", DNA).unwrap();
+    writeln!(
+        out,
+        "    <sbol:type rdf:resource=\"{}\"/>",
+        if circular { CIRCULAR } else { LINEAR }
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    <sbol:role rdf:resource=\"{}\"/>",
+        ENGINEERED_REGION
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "    <sbol:hasSequence rdf:resource=\"{}\"/>",
+        product_seq_uri
+    )
+    .unwrap();
+    let mut ranges = 0;
+    for (i, p) in parts.iter().enumerate() {
+        let sub_uri = format!("{}/SubComponent{}", product_uri, i + 1);
+        let orientation = if p.fwd { INLINE } else { REVERSE_COMPLEMENT };
+        writeln!(out, "    <sbol:hasFeature>").unwrap();
+        writeln!(out, "      <sbol:SubComponent rdf:about=\"{}\">", sub_uri).unwrap();
+        writeln!(
+            out,
+            "      <sbol:displayId>SubComponent{}</sbol:displayId>",
+            i + 1
+        )
+        .unwrap();
+        writeln!(out, "      <sbol:name>{}</sbol:name>", part_ids[i]).unwrap();
+        writeln!(
+            out,
+            "      <sbol:instanceOf rdf:resource=\"{}\"/>",
+            component_of(p.seq)
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:orientation rdf:resource=\"{}\"/>",
+            orientation
+        )
+        .unwrap();
+        let len = p.end - p.start;
+        write_ranges(
+            &mut out,
+            &sub_uri,
+            "hasLocation",
+            &mut ranges,
+            &product_seq_uri,
+            p.pos,
+            len,
+            total,
+            INLINE,
+        );
+        let source_start = p.source_start();
+        let source_seq_uri = format!("{}_sequence", component_of(p.seq));
+        write_ranges(
+            &mut out,
+            &sub_uri,
+            "sourceLocation",
+            &mut ranges,
+            &source_seq_uri,
+            source_start,
+            len,
+            p.seq.seq.len(),
+            INLINE,
+        );
+        writeln!(out, "      </sbol:SubComponent>").unwrap();
+        writeln!(out, "    </sbol:hasFeature>").unwrap();
+    }
+    // the overlaps, each one joining part `i - 1` to part `i`
+    for (i, p) in parts.iter().enumerate() {
+        if p.overlap == 0 {
+            continue;
+        }
+        let prev = if i == 0 { parts.len() - 1 } else { i - 1 };
+        let uri = format!("{}/Overlap{}", product_uri, i + 1);
+        writeln!(out, "    <sbol:hasFeature>").unwrap();
+        writeln!(out, "      <sbol:SequenceFeature rdf:about=\"{}\">", uri).unwrap();
+        writeln!(
+            out,
+            "      <sbol:displayId>Overlap{}</sbol:displayId>",
+            i + 1
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:name>overlap {} / {}</sbol:name>",
+            part_ids[prev], part_ids[i]
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:role rdf:resource=\"{}\"/>",
+            CONSERVED_REGION
+        )
+        .unwrap();
+        write_ranges(
+            &mut out,
+            &uri,
+            "hasLocation",
+            &mut ranges,
+            &product_seq_uri,
+            p.pos,
+            p.overlap,
+            total,
+            INLINE,
+        );
+        writeln!(out, "      </sbol:SequenceFeature>").unwrap();
+        writeln!(out, "    </sbol:hasFeature>").unwrap();
+    }
+    for i in 0..parts.len() {
+        let next = i + 1;
+        if next == parts.len() && !circular {
+            break;
+        }
+        let next = next % parts.len();
+        if next == i {
+            break;
+        }
+        let uri = format!("{}/Constraint{}", product_uri, i + 1);
+        writeln!(out, "    <sbol:hasConstraint>").unwrap();
+        writeln!(out, "      <sbol:Constraint rdf:about=\"{}\">", uri).unwrap();
+        writeln!(
+            out,
+            "      <sbol:displayId>Constraint{}</sbol:displayId>",
+            i + 1
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:restriction rdf:resource=\"{}overlaps\"/>",
+            SBOL
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:subject rdf:resource=\"{}/SubComponent{}\"/>",
+            product_uri,
+            i + 1
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "      <sbol:object rdf:resource=\"{}/SubComponent{}\"/>",
+            product_uri,
+            next + 1
+        )
+        .unwrap();
+        writeln!(out, "      </sbol:Constraint>").unwrap();
+        writeln!(out, "    </sbol:hasConstraint>").unwrap();
+    }
+    writeln!(out, "  </sbol:Component>").unwrap();
+    write_sequence(&mut out, &product_seq_uri, &product_seq);
+
+    for &(seq, id) in &components {
+        let uri = format!("{}/{}", namespace, id);
+        writeln!(out, "  <sbol:Component rdf:about=\"{}\">", uri).unwrap();
+        writeln!(out, "    <sbol:displayId>{}</sbol:displayId>", id).unwrap();
+        if let Some(name) = &seq.name {
+            writeln!(out, "    <sbol:name>{}</sbol:name>", escape(name)).unwrap();
+        }
+        if let Some(definition) = &seq.definition {
+            writeln!(
+                out,
+                "    <sbol:description>{}</sbol:description>",
+                escape(definition)
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "    <sbol:hasNamespace rdf:resource=\"{}\"/>",
+            namespace
+        )
+        .unwrap();
+        writeln!(out, "    <sbol:type rdf:resource=\"{}\"/>", DNA).unwrap();
+        writeln!(
+            out,
+            "    <sbol:type rdf:resource=\"{}\"/>",
+            if seq.is_circular() { CIRCULAR } else { LINEAR }
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    <sbol:hasSequence rdf:resource=\"{}_sequence\"/>",
+            uri
+        )
+        .unwrap();
+        writeln!(out, "  </sbol:Component>").unwrap();
+        write_sequence(&mut out, &format!("{}_sequence", uri), &seq.seq);
+    }
+    writeln!(out, "</rdf:RDF>").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let a = Seq {
+            name: Some("frag A".into()),
+            seq: b"acgtacgtttggccaa".to_vec(),
+            ..Seq::empty()
+        };
+        let b = Seq {
+            name: Some("fragB".into()),
+            seq: b"ccccttggccaa".to_vec(),
+            ..Seq::empty()
+        };
+        let path = vec![
+            Node(MatchIdx::Idx(0), Match(0, 0, 0)),
+            Node(MatchIdx::IdxRc(1), Match(8, 0, 8)),
+        ];
+        let res = export_product(
+            "https://example.com/",
+            "frag A, fragB[rev]",
+            &[&a, &b],
+            &path,
+        );
+        assert!(res.contains("<sbol:elements>acgtacgtttggccaagggg</sbol:elements>"));
+        assert!(res.contains(&format!("<sbol:type rdf:resource=\"{}\"/>", LINEAR)));
+        assert!(res.contains("rdf:about=\"https://example.com/frag_A\""));
+        assert!(res.contains(&format!(
+            "<sbol:orientation rdf:resource=\"{}\"/>",
+            REVERSE_COMPLEMENT
+        )));
+        // the second part covers 9..20 of the product
+        assert!(res.contains("<sbol:start>9</sbol:start>\n          <sbol:end>20</sbol:end>"));
+        // and the overlap 9..16
+        assert!(res.contains("<sbol:start>9</sbol:start>\n          <sbol:end>16</sbol:end>"));
+        assert!(roxmltree::Document::parse(&res).is_ok());
+    }
+}
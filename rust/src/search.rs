@@ -1,15 +1,17 @@
+use bio::alphabets::dna;
 use gb_io::seq::*;
 use pcr::Annealer;
 use std::cmp;
+use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
 
 #[derive(Serialize, Clone, Debug, PartialEq, TypescriptDefinition)]
 #[serde(rename_all = "camelCase")]
 pub struct SeqSearchResult {
-    start: i64,
-    end: i64,
-    fwd: bool,
+    pub(crate) start: i64,
+    pub(crate) end: i64,
+    pub(crate) fwd: bool,
 }
 
 /// reuse the pcr crate to do a fast, circular sequence search
@@ -46,6 +48,75 @@ pub fn search(s: &Seq, query: &[u8], max_res: usize, rc: bool) -> Vec<SeqSearchR
     res
 }
 
+/// Search allowing up to `mismatches` substitutions. The query is split into
+/// `mismatches + 1` pieces, at least one of which has to match exactly, so
+/// `search` finds the candidates. Returns each match with its number of
+/// mismatches, at most `max_res + 1` of them, like `search`.
+pub fn search_mismatches(
+    s: &Seq,
+    query: &[u8],
+    mismatches: usize,
+    max_res: usize,
+) -> Vec<(SeqSearchResult, usize)> {
+    let len = s.len();
+    let qlen = query.len();
+    let circular = s.is_circular();
+    if len == 0 || qlen == 0 || qlen as i64 > len {
+        return Vec::new();
+    }
+    let query = query.to_ascii_uppercase();
+    let rc = dna::revcomp(&query);
+    let pieces = cmp::min(mismatches + 1, qlen);
+    let mut seen = HashSet::new();
+    let mut res = Vec::new();
+    'pieces: for p in 0..pieces {
+        let (from, to) = (p * qlen / pieces, (p + 1) * qlen / pieces);
+        // a piece can't match more than once per position and strand, so
+        // this finds all of its matches
+        for hit in search(s, &query[from..to], 2 * len as usize, true) {
+            let start = if hit.fwd {
+                hit.start - from as i64
+            } else {
+                hit.start - (qlen - to) as i64
+            };
+            let start = if circular {
+                start.rem_euclid(len)
+            } else {
+                start
+            };
+            if start < 0 || (!circular && start + qlen as i64 > len) {
+                continue;
+            }
+            if !seen.insert((start, hit.fwd)) {
+                continue;
+            }
+            let target = if hit.fwd { &query } else { &rc };
+            let n = target
+                .iter()
+                .enumerate()
+                .filter(|&(i, &c)| {
+                    s.seq[((start + i as i64) % len) as usize].to_ascii_uppercase() != c
+                })
+                .count();
+            if n <= mismatches {
+                res.push((
+                    SeqSearchResult {
+                        start,
+                        end: start + qlen as i64,
+                        fwd: hit.fwd,
+                    },
+                    n,
+                ));
+                if res.len() > max_res {
+                    break 'pieces;
+                }
+            }
+        }
+    }
+    res.sort_by_key(|(r, _)| (r.start, !r.fwd));
+    res
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -141,6 +212,56 @@ mod test {
         );
     }
     #[test]
+    fn test_search_mismatches() {
+        let s = Seq {
+            seq: b"ttttGACTACAAAGACGATGACGACAAGtttt".to_vec(),
+            topology: Topology::Circular,
+            ..Seq::empty()
+        };
+        // two mismatches, and across the origin
+        let query = b"ACAAGTTTTATTAGACTACAAAGACGA";
+        assert!(search_mismatches(&s, query, 1, 10).is_empty());
+        assert_eq!(
+            search_mismatches(&s, query, 2, 10),
+            vec![(
+                SeqSearchResult {
+                    start: 23,
+                    end: 50,
+                    fwd: true
+                },
+                2
+            )]
+        );
+        let rc = dna::revcomp(&query[..]);
+        let res = search_mismatches(&s, &rc, 2, 10);
+        assert_eq!(res.len(), 1);
+        assert_eq!((res[0].0.start, res[0].0.fwd, res[0].1), (23, false, 2));
+        // the only exact piece matches more than 10000 times
+        let s = Seq {
+            seq: b"A".iter().cycle().take(12_000).chain(b"CGTCC").cloned().collect(),
+            ..Seq::empty()
+        };
+        assert_eq!(
+            search_mismatches(&s, b"AAAAACGTCG", 1, 10),
+            vec![(
+                SeqSearchResult {
+                    start: 11_995,
+                    end: 12_005,
+                    fwd: true
+                },
+                1
+            )]
+        );
+        // results are sorted when the search ends early too
+        let s = Seq {
+            seq: b"GTTTCCCCGAAACC".to_vec(),
+            ..Seq::empty()
+        };
+        let res = search_mismatches(&s, b"AAAC", 0, 1);
+        let starts: Vec<_> = res.iter().map(|(r, _)| (r.start, r.fwd)).collect();
+        assert_eq!(starts, vec![(0, false), (9, true)]);
+    }
+    #[test]
     fn test_search_circ() {
         let s = Seq {
             // 78 bytes
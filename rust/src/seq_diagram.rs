@@ -1,3 +1,4 @@
+use crate::location::for_each_range;
 use bio::data_structures::interval_tree::IntervalTree;
 use gb_io::seq::*;
 use std::collections::{HashMap, HashSet};
@@ -6,20 +7,6 @@ use wasm_bindgen::prelude::*;
 
 pub fn get_diagram_data(t: &Seq, subset: Option<Vec<usize>>) -> Box<[JsValue]> {
     const DIRECTIONAL: &[FeatureKind] = &[feature_kind!("CDS"), feature_kind!("gene")];
-    // This can't be done in a closure because it's recursive
-    fn range_finder<'a>(p: &'a Location, closure: &mut impl FnMut(i64, i64, bool), fwd: bool) {
-        use Location::*;
-        match *p {
-            Complement(ref p) => range_finder(p, closure, !fwd),
-            Join(ref ps) | OneOf(ref ps) | Bond(ref ps) | Order(ref ps) => {
-                for p in ps {
-                    range_finder(p, closure, fwd);
-                }
-            }
-            Range((a, _), (b, _)) => closure(a, b, fwd),
-            _ => {}
-        }
-    }
     struct ArrowIntervals<'a> {
         idx: usize,
         start: i64,
@@ -90,7 +77,7 @@ pub fn get_diagram_data(t: &Seq, subset: Option<Vec<usize>>) -> Box<[JsValue]> {
             };
             arrows.push(a);
         };
-        range_finder(&f.location, &mut add_span, true);
+        for_each_range(&f.location, &mut add_span, true);
     }
     arrows.sort_by(|b, a| (a.end - a.start).cmp(&(b.end - b.start))); // sort by length
     /// Final output for js
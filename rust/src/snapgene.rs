@@ -0,0 +1,288 @@
+use gb_io::seq::*;
+use std::str;
+
+// packet types, see the "SnapGene File Format" document
+const PACKET_DNA: u8 = 0x00;
+const PACKET_PRIMERS: u8 = 0x05;
+const PACKET_NOTES: u8 = 0x06;
+const PACKET_COOKIE: u8 = 0x09;
+const PACKET_FEATURES: u8 = 0x0A;
+
+/// Split a SnapGene file into `(type, data)` packets
+fn packets(mut data: &[u8]) -> Result<Vec<(u8, &[u8])>, String> {
+    let mut res = Vec::new();
+    while !data.is_empty() {
+        if data.len() < 5 {
+            return Err("Truncated packet header".into());
+        }
+        let kind = data[0];
+        let len = u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize;
+        data = &data[5..];
+        if data.len() < len {
+            return Err(format!("Truncated packet of type {}", kind));
+        }
+        res.push((kind, &data[..len]));
+        data = &data[len..];
+    }
+    Ok(res)
+}
+
+fn xml_packet(data: &[u8]) -> Result<roxmltree::Document, String> {
+    let text = str::from_utf8(data).map_err(|e| format!("Invalid UTF-8 in XML packet: {}", e))?;
+    roxmltree::Document::parse(text).map_err(|e| format!("Invalid XML packet: {}", e))
+}
+
+/// Parse a SnapGene `"start-end"` range (1-based, inclusive) into a `Location`.
+/// `start > end` means that the range spans the origin of a circular sequence.
+fn parse_range(range: &str, len: i64, circular: bool) -> Result<Location, String> {
+    let mut parts = range.splitn(2, '-').map(|p| p.trim().parse::<i64>());
+    let (start, end) = match (parts.next(), parts.next()) {
+        (Some(Ok(start)), Some(Ok(end))) if start >= 1 && end >= 1 => (start - 1, end),
+        _ => return Err(format!("Invalid range: {}", range)),
+    };
+    if start >= len || end > len {
+        Err(format!(
+            "Range {} is outside of the sequence (length {})",
+            range, len
+        ))
+    } else if start < end {
+        Ok(Location::simple_range(start, end))
+    } else if !circular {
+        Err(format!(
+            "Range {} spans the origin of a linear sequence",
+            range
+        ))
+    } else if start == end {
+        // e.g. `6-5`, neither a single base nor a proper subrange
+        Err(format!("Range {} wraps around the whole sequence", range))
+    } else {
+        Ok(Location::Join(vec![
+            Location::simple_range(start, len),
+            Location::simple_range(0, end),
+        ]))
+    }
+}
+
+fn parse_features(doc: &roxmltree::Document, seq: &Seq) -> Result<Vec<Feature>, String> {
+    let mut res = Vec::new();
+    for f in doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("Feature"))
+    {
+        let mut qualifiers = Vec::new();
+        if let Some(name) = f.attribute("name") {
+            qualifiers.push((qualifier_key!("label"), Some(name.to_string())));
+        }
+        let mut ranges = Vec::new();
+        let mut colour = None;
+        for segment in f.children().filter(|n| n.has_tag_name("Segment")) {
+            if let Some(range) = segment.attribute("range") {
+                ranges.push(parse_range(range, seq.len(), seq.is_circular())?);
+            }
+            if colour.is_none() {
+                colour = segment.attribute("color");
+            }
+        }
+        for q in f.children().filter(|n| n.has_tag_name("Q")) {
+            let key = match q.attribute("name") {
+                Some(key) => QualifierKey::from(key),
+                None => continue,
+            };
+            for v in q.children().filter(|n| n.has_tag_name("V")) {
+                let val = v
+                    .attribute("text")
+                    .or_else(|| v.attribute("int"))
+                    .or_else(|| v.attribute("predef"));
+                qualifiers.push((key.clone(), val.map(String::from)));
+            }
+        }
+        let reverse = f.attribute("directionality") == Some("2");
+        if let Some(colour) = colour {
+            let key = if reverse {
+                QualifierKey::from("ApEinfo_revcolor")
+            } else {
+                QualifierKey::from("ApEinfo_fwdcolor")
+            };
+            qualifiers.push((key, Some(colour.to_string())));
+        }
+        let mut location = match ranges.len() {
+            0 => return Err("Feature without location".into()),
+            1 => ranges.pop().unwrap(),
+            _ => Location::Join(ranges),
+        };
+        if reverse {
+            location = Location::Complement(Box::new(location));
+        }
+        res.push(Feature {
+            kind: FeatureKind::from(f.attribute("type").unwrap_or("misc_feature")),
+            location,
+            qualifiers,
+        });
+    }
+    Ok(res)
+}
+
+fn parse_primers(doc: &roxmltree::Document, seq: &Seq) -> Result<Vec<Feature>, String> {
+    let mut res = Vec::new();
+    for p in doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("Primer"))
+    {
+        // each binding site may be listed twice, once with `simplified="1"`
+        let mut sites = Vec::new();
+        for site in p.children().filter(|n| n.has_tag_name("BindingSite")) {
+            if let Some(range) = site.attribute("location") {
+                let key = (range, site.attribute("boundStrand") == Some("1"));
+                if !sites.contains(&key) {
+                    sites.push(key);
+                }
+            }
+        }
+        for (range, reverse) in sites {
+            let mut location = parse_range(range, seq.len(), seq.is_circular())?;
+            if reverse {
+                location = Location::Complement(Box::new(location));
+            }
+            let mut qualifiers = Vec::new();
+            if let Some(name) = p.attribute("name") {
+                qualifiers.push((qualifier_key!("label"), Some(name.to_string())));
+            }
+            if let Some(desc) = p.attribute("description") {
+                qualifiers.push((qualifier_key!("note"), Some(desc.to_string())));
+            }
+            res.push(Feature {
+                kind: FeatureKind::from("primer_bind"),
+                location,
+                qualifiers,
+            });
+        }
+    }
+    Ok(res)
+}
+
+fn apply_notes(doc: &roxmltree::Document, seq: &mut Seq) {
+    for n in doc.root_element().children().filter(|n| n.is_element()) {
+        let text = match n.text() {
+            Some(text) if !text.trim().is_empty() => text.trim().to_string(),
+            _ => continue,
+        };
+        match n.tag_name().name() {
+            "Description" => seq.definition = Some(text),
+            "AccessionNumber" => seq.accession = Some(text),
+            "Comments" => seq.comments.push(text),
+            _ => {}
+        }
+    }
+}
+
+/// Decode a SnapGene `.dna` file
+pub fn parse(data: &[u8]) -> Result<Seq, String> {
+    let packets = packets(data)?;
+    match packets.first() {
+        Some(&(PACKET_COOKIE, cookie)) if cookie.starts_with(b"SnapGene") => {}
+        _ => return Err("Not a SnapGene file".into()),
+    }
+    let mut seq = Seq::empty();
+    let (flags, dna) = packets
+        .iter()
+        .find(|&&(kind, _)| kind == PACKET_DNA)
+        .and_then(|&(_, data)| data.split_first())
+        .ok_or_else(|| String::from("No DNA packet found"))?;
+    seq.seq = dna.to_vec();
+    if flags & 0x01 != 0 {
+        seq.topology = Topology::Circular;
+    }
+    let mut features = Vec::new();
+    for &(kind, data) in &packets {
+        match kind {
+            PACKET_FEATURES => features.extend(parse_features(&xml_packet(data)?, &seq)?),
+            PACKET_PRIMERS => features.extend(parse_primers(&xml_packet(data)?, &seq)?),
+            PACKET_NOTES => apply_notes(&xml_packet(data)?, &mut seq),
+            _ => {}
+        }
+    }
+    seq.features = features;
+    Ok(seq)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn packet(kind: u8, data: &[u8]) -> Vec<u8> {
+        let mut res = vec![kind];
+        res.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        res.extend_from_slice(data);
+        res
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut data = packet(PACKET_COOKIE, b"SnapGene\x00\x01\x00\x0f\x00\x13");
+        data.extend(packet(PACKET_DNA, b"\x01ACGTACGTAC"));
+        data.extend(packet(
+            PACKET_FEATURES,
+            br##"<Features><Feature name="f1" directionality="2" type="CDS"><Segment range="9-2" color="#ff0000"/><Q name="note"><V text="hello"/></Q></Feature></Features>"##,
+        ));
+        data.extend(packet(
+            PACKET_PRIMERS,
+            br#"<Primers><Primer name="p1" sequence="ACGT"><BindingSite location="1-4" boundStrand="0"/><BindingSite location="1-4" boundStrand="0" simplified="1"/></Primer></Primers>"#,
+        ));
+        data.extend(packet(
+            PACKET_NOTES,
+            b"<Notes><Description>A test</Description></Notes>",
+        ));
+        let seq = parse(&data).unwrap();
+        assert_eq!(seq.seq, b"ACGTACGTAC".to_vec());
+        assert!(seq.is_circular());
+        assert_eq!(seq.definition, Some("A test".into()));
+        assert_eq!(seq.features.len(), 2);
+        assert_eq!(
+            seq.features[0].location,
+            Location::Complement(Box::new(Location::Join(vec![
+                Location::simple_range(8, 10),
+                Location::simple_range(0, 2)
+            ])))
+        );
+        assert_eq!(
+            seq.features[0]
+                .qualifier_values(qualifier_key!("note"))
+                .next(),
+            Some("hello")
+        );
+        assert_eq!(
+            seq.features[0]
+                .qualifier_values(QualifierKey::from("ApEinfo_revcolor"))
+                .next(),
+            Some("#ff0000")
+        );
+        assert_eq!(seq.features[1].kind, FeatureKind::from("primer_bind"));
+        assert_eq!(seq.features[1].location, Location::simple_range(0, 4));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("5-5", 10, false),
+            Ok(Location::simple_range(4, 5))
+        );
+        assert!(parse_range("8-11", 10, false).is_err());
+        assert!(parse_range("11-2", 10, true).is_err());
+        assert!(parse_range("9-2", 10, false).is_err());
+        assert!(parse_range("6-5", 10, true).is_err());
+        assert_eq!(
+            parse_range("10-1", 10, true),
+            Ok(Location::Join(vec![
+                Location::simple_range(9, 10),
+                Location::simple_range(0, 1)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_not_snapgene() {
+        assert!(parse(b"LOCUS       test").is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Copying features from an annotated sequence to a homologous one. The
+//! region each feature spans is located in the target with shared k-mers
+//! (on both strands, across the origin of circular targets), aligned to
+//! check its identity, and the feature's ranges are mapped through the
+//! alignment.
+use crate::diff::{align, Op};
+use crate::location::{for_each_range, split_at_origin, strand_range};
+use bio::alphabets::dna;
+use gb_io::seq::*;
+use std::collections::HashMap;
+
+const K: usize = 12;
+/// Maximum number of places to try aligning each feature
+const MAX_CANDIDATES: usize = 10;
+/// K-mers occurring more often than this in the target, e.g. in repeats or
+/// low-complexity sequence, don't vote for candidates
+const MAX_KMER_HITS: usize = 100;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferSettings {
+    /// percentage
+    #[serde(default = "default_min_identity")]
+    pub min_identity: f64,
+    /// shorter features match too many places to be transferred reliably
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+}
+
+fn default_min_identity() -> f64 {
+    90.0
+}
+
+fn default_min_length() -> usize {
+    20
+}
+
+impl Default for TransferSettings {
+    fn default() -> TransferSettings {
+        TransferSettings {
+            min_identity: default_min_identity(),
+            min_length: default_min_length(),
+        }
+    }
+}
+
+/// The k-mers of one strand of the target, extended past the end if it is
+/// circular
+struct Strand {
+    bases: Vec<u8>,
+    kmers: HashMap<Vec<u8>, Vec<usize>>,
+}
+
+impl Strand {
+    fn new(bases: Vec<u8>, circular: bool) -> Strand {
+        let mut extended = bases.clone();
+        if circular {
+            let wrap = (K - 1).min(bases.len());
+            extended.extend_from_slice(&bases[..wrap]);
+        }
+        let mut kmers: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        if extended.len() >= K {
+            for j in 0..=extended.len() - K {
+                kmers
+                    .entry(extended[j..j + K].to_vec())
+                    .or_insert_with(Vec::new)
+                    .push(j);
+            }
+        }
+        Strand { bases, kmers }
+    }
+
+    /// `len` bases starting at `start`, wrapping around if `circular`
+    fn window(&self, start: i64, len: usize, circular: bool) -> Vec<u8> {
+        let n = self.bases.len() as i64;
+        if circular {
+            (0..len as i64)
+                .map(|i| self.bases[(start + i).rem_euclid(n) as usize])
+                .collect()
+        } else {
+            let start = start.max(0).min(n) as usize;
+            let end = (start + len).min(n as usize);
+            self.bases[start..end].to_vec()
+        }
+    }
+}
+
+/// The ranges of a feature as `(start, end, fwd)`, unwrapped so they lie
+/// within one span starting at the first of them
+fn feature_ranges(seq: &Seq, f: &Feature) -> Vec<(i64, i64, bool)> {
+    let mut ranges = Vec::new();
+    for_each_range(&f.location, &mut |a, b, fwd| ranges.push((a, b, fwd)), true);
+    let len = seq.len();
+    if !seq.is_circular() || ranges.len() < 2 || len == 0 {
+        return ranges;
+    }
+    // the span starts after the largest gap between the ranges
+    let mut starts: Vec<(i64, i64)> = ranges.iter().map(|&(a, b, _)| (a, b)).collect();
+    starts.sort();
+    let mut span_start = starts[0].0;
+    let mut max_gap = starts[0].0 + len - starts.iter().map(|r| r.1).max().unwrap();
+    for w in starts.windows(2) {
+        let gap = w[1].0 - w[0].1;
+        if gap > max_gap {
+            max_gap = gap;
+            span_start = w[1].0;
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(a, b, fwd)| {
+            if a < span_start {
+                (a + len, b + len, fwd)
+            } else {
+                (a, b, fwd)
+            }
+        })
+        .collect()
+}
+
+/// For each position in `a` (including its end), the corresponding
+/// position in `b`
+fn position_map(ops: &[Op], a_len: usize) -> Vec<i64> {
+    let mut res = Vec::with_capacity(a_len + 1);
+    let mut j = 0;
+    for op in ops {
+        match op {
+            Op::Match | Op::Subst => {
+                res.push(j);
+                j += 1;
+            }
+            Op::Del => res.push(j),
+            Op::Ins => j += 1,
+        }
+    }
+    res.push(j);
+    res
+}
+
+/// Candidate `(reversed, diagonal)` pairs for `query`, best first. On
+/// circular targets diagonals are taken modulo `len`.
+fn candidates(query: &[u8], strands: &[Strand; 2], circular: bool) -> Vec<(bool, i64)> {
+    let mut votes: HashMap<(bool, i64), usize> = HashMap::new();
+    for i in 0..=query.len() - K {
+        for (s, strand) in strands.iter().enumerate() {
+            if let Some(hits) = strand.kmers.get(&query[i..i + K]) {
+                if hits.len() > MAX_KMER_HITS {
+                    continue;
+                }
+                let len = strand.bases.len() as i64;
+                for &j in hits {
+                    let d = j as i64 - i as i64;
+                    let d = if circular { d.rem_euclid(len) } else { d };
+                    *votes.entry((s == 1, d)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    let mut votes: Vec<_> = votes.into_iter().collect();
+    votes.sort_by_key(|&((rev, d), n)| (std::cmp::Reverse(n), rev, d));
+    // nearby diagonals are the same hit, shifted by indels
+    let mut res: Vec<(bool, i64)> = Vec::new();
+    for ((rev, d), _) in votes {
+        if res.len() == MAX_CANDIDATES {
+            break;
+        }
+        if !res
+            .iter()
+            .any(|&(r, e)| r == rev && (e - d).abs() < query.len() as i64 / 2)
+        {
+            res.push((rev, d));
+        }
+    }
+    res
+}
+
+/// The location of the mapped `ranges`, split where they cross the origin
+/// of a target of length `len`
+fn transferred_location(ranges: &[(i64, i64, bool)], len: i64) -> Location {
+    match ranges {
+        [(a, b, fwd)] => strand_range(*a, *b, *fwd, len),
+        _ if ranges.iter().all(|r| !r.2) => Location::Complement(Box::new(Location::Join(
+            ranges
+                .iter()
+                .flat_map(|&(a, b, _)| split_at_origin(a, b, len))
+                .collect(),
+        ))),
+        _ => Location::Join(
+            ranges
+                .iter()
+                .flat_map(|&(a, b, fwd)| {
+                    let mut pieces = split_at_origin(a, b, len);
+                    if !fwd {
+                        pieces.reverse();
+                        pieces = pieces
+                            .into_iter()
+                            .map(|l| Location::Complement(Box::new(l)))
+                            .collect();
+                    }
+                    pieces
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Copy the features of `source` onto matching regions of `target`, each to
+/// the place it matches with the highest identity
+pub fn transfer(source: &Seq, target: &Seq, settings: &TransferSettings) -> Seq {
+    let mut res = target.clone();
+    let len = target.len();
+    let circular = target.is_circular();
+    let fwd = target.seq.to_ascii_uppercase();
+    let rc = Strand::new(dna::revcomp(&fwd), circular);
+    let strands = [Strand::new(fwd, circular), rc];
+    let source_bases = source.seq.to_ascii_uppercase();
+    let source_len = source.len();
+    let source_name = source.name.as_ref().map(String::as_str).unwrap_or("source");
+    for f in &source.features {
+        let ranges = feature_ranges(source, f);
+        let start = match ranges.iter().map(|r| r.0).min() {
+            Some(start) => start,
+            None => continue,
+        };
+        let end = ranges.iter().map(|r| r.1).max().unwrap();
+        let span = (end - start) as usize;
+        if span < settings.min_length.max(K) || start < 0 || end > 2 * source_len || len == 0 {
+            continue;
+        }
+        let query: Vec<u8> = (start..end)
+            .map(|i| source_bases[(i % source_len) as usize])
+            .collect();
+        let mut best: Option<(f64, Location)> = None;
+        for (reversed, diagonal) in candidates(&query, &strands, circular) {
+            let strand = &strands[reversed as usize];
+            // linear targets are cut off at the ends
+            let diagonal = if circular { diagonal } else { diagonal.max(0) };
+            let window = strand.window(diagonal, span, circular);
+            let ops = match align(&query, &window) {
+                Ok(ops) => ops,
+                Err(_) => continue,
+            };
+            let matches = ops.iter().filter(|&&o| o == Op::Match).count();
+            let identity = 100.0 * matches as f64 / ops.len() as f64;
+            if identity < settings.min_identity {
+                continue;
+            }
+            let map = position_map(&ops, query.len());
+            let mut new_ranges: Vec<(i64, i64, bool)> = ranges
+                .iter()
+                .filter_map(|&(a, b, fwd)| {
+                    let (a, b) = (map[(a - start) as usize], map[(b - start) as usize]);
+                    if a >= b {
+                        return None;
+                    }
+                    // window coordinates to target coordinates
+                    let (a, b) = if reversed {
+                        (len - (diagonal + b), len - (diagonal + a))
+                    } else {
+                        (diagonal + a, diagonal + b)
+                    };
+                    let a_norm = if circular { a.rem_euclid(len) } else { a };
+                    Some((a_norm, a_norm + b - a, fwd != reversed))
+                })
+                .collect();
+            if new_ranges.is_empty() {
+                continue;
+            }
+            if reversed {
+                new_ranges.reverse();
+            }
+            if best.as_ref().map_or(true, |b| identity > b.0) {
+                best = Some((identity, transferred_location(&new_ranges, len)));
+            }
+        }
+        let (identity, location) = match best {
+            Some(best) => best,
+            None => continue,
+        };
+        if res
+            .features
+            .iter()
+            .any(|g| g.kind == f.kind && g.location == location)
+        {
+            continue;
+        }
+        let mut qualifiers = f.qualifiers.clone();
+        qualifiers.push((
+            qualifier_key!("note"),
+            Some(format!(
+                "transferred from {} ({:.1}% identity)",
+                source_name, identity
+            )),
+        ));
+        res.features.push(Feature {
+            kind: f.kind.clone(),
+            location,
+            qualifiers,
+        });
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    const GENE: &[u8] = b"ATGACCATGATTACGGATTCACTGGCCGTCGTTTTACAACGTCGTGACTGGGAAAACCCTGGCG";
+
+    fn seq(name: &str, bases: &[u8], circular: bool, locations: &[&str]) -> Seq {
+        Seq {
+            name: Some(name.into()),
+            topology: if circular {
+                Topology::Circular
+            } else {
+                Topology::Linear
+            },
+            seq: bases.to_vec(),
+            features: locations
+                .iter()
+                .map(|l| Feature {
+                    kind: feature_kind!("CDS"),
+                    location: location::parse(l).unwrap(),
+                    qualifiers: vec![(qualifier_key!("gene"), Some("lacZ".into()))],
+                })
+                .collect(),
+            ..Seq::empty()
+        }
+    }
+
+    fn locations(seq: &Seq) -> Vec<String> {
+        seq.features
+            .iter()
+            .map(|f| location::format(&f.location))
+            .collect()
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut source_bases = b"ccccc".to_vec();
+        source_bases.extend_from_slice(GENE);
+        let source = seq("pSource", &source_bases, false, &["6..69", "6..10"]);
+        // reverse complement with a mismatch, in a larger sequence
+        let mut target_bases = GENE.to_vec();
+        target_bases[30] = b'A';
+        let mut target_bases = dna::revcomp(&target_bases);
+        target_bases.splice(0..0, b"tttttttttt".iter().cloned());
+        let target = seq("target", &target_bases, false, &[]);
+        let res = transfer(&source, &target, &TransferSettings::default());
+        assert_eq!(locations(&res), vec!["complement(11..74)"]);
+        assert_eq!(
+            res.features[0]
+                .qualifier_values(qualifier_key!("note"))
+                .next(),
+            Some("transferred from pSource (98.4% identity)")
+        );
+        // nothing to add the second time
+        let again = transfer(&source, &res, &TransferSettings::default());
+        assert_eq!(again.features.len(), 1);
+    }
+
+    #[test]
+    fn test_origin() {
+        let source = seq("source", GENE, false, &["join(1..10,21..40)"]);
+        let mut target_bases = GENE.to_vec();
+        target_bases.rotate_left(30);
+        let target = seq("target", &target_bases, true, &[]);
+        let res = transfer(&source, &target, &TransferSettings::default());
+        // 1..10 is at 35..44, 21..30 at 55..64 and 31..40 at 1..10
+        assert_eq!(locations(&res), vec!["join(35..44,55..64,1..10)"]);
+    }
+
+    #[test]
+    fn test_best_placement() {
+        let source = seq("source", GENE, false, &["1..64"]);
+        // a copy with a mismatch, then an exact one
+        let mut target_bases = GENE.to_vec();
+        target_bases[30] = b'A';
+        target_bases.extend_from_slice(b"tttttttttt");
+        target_bases.extend_from_slice(GENE);
+        let target = seq("target", &target_bases, false, &[]);
+        let res = transfer(&source, &target, &TransferSettings::default());
+        assert_eq!(locations(&res), vec!["75..138"]);
+        // low-complexity sequence has no usable k-mers
+        let source = seq("source", &[b'A'; 40], false, &["1..40"]);
+        let target = seq("target", &[b'A'; 200], false, &[]);
+        let res = transfer(&source, &target, &TransferSettings::default());
+        assert!(res.features.is_empty());
+    }
+}
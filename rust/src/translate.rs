@@ -0,0 +1,225 @@
+//! Translating ranges and features with the tables in `genetic_code`. Feature
+//! translations follow the INSDC conventions: `/codon_start` and
+//! `/transl_table` are honoured, the first codon is translated as a start
+//! codon unless the feature is 5' partial, and a final stop is left out.
+use crate::genetic_code::{self, GeneticCode};
+use bio::alphabets::dna;
+use gb_io::seq::*;
+
+/// The result of translating a feature, compared with its `/translation`
+/// qualifier if it has one
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureTranslation {
+    pub protein: String,
+    pub table: u8,
+    pub expected: Option<String>,
+    /// index of the first residue differing from `expected`, `None` if they
+    /// are the same or there is no `/translation`
+    pub first_mismatch: Option<usize>,
+}
+
+/// The bases from `start` to `end`, which may extend past the end of a
+/// circular sequence
+fn range_bases(seq: &Seq, start: i64, end: i64) -> Result<Vec<u8>, String> {
+    let len = seq.len();
+    let in_bounds = if seq.is_circular() {
+        start >= 0 && start <= end && end - start <= len && start < len.max(1)
+    } else {
+        start >= 0 && start <= end && end <= len
+    };
+    if !in_bounds {
+        return Err(format!("Invalid range: {}..{}", start, end));
+    }
+    Ok((start..end).map(|i| seq.seq[(i % len) as usize]).collect())
+}
+
+/// The bases of `l` in the direction of its strand
+fn location_bases(seq: &Seq, l: &Location) -> Result<Vec<u8>, String> {
+    use Location::*;
+    match *l {
+        Range((a, _), (b, _)) => range_bases(seq, a, b),
+        Between(..) => Ok(Vec::new()),
+        Complement(ref l) => Ok(dna::revcomp(&location_bases(seq, l)?)),
+        Join(ref ls) | Order(ref ls) => {
+            let mut res = Vec::new();
+            for l in ls {
+                res.extend(location_bases(seq, l)?);
+            }
+            Ok(res)
+        }
+        _ => Err(format!(
+            "Can't translate location: {}",
+            crate::location::format(l)
+        )),
+    }
+}
+
+/// Whether `l` is partial at its 5' and 3' ends
+fn partial_ends(l: &Location) -> (bool, bool) {
+    use Location::*;
+    match *l {
+        Range((_, Before(before)), (_, After(after))) => (before, after),
+        Complement(ref l) => {
+            let (five, three) = partial_ends(l);
+            (three, five)
+        }
+        Join(ref ls) | Order(ref ls) if !ls.is_empty() => {
+            (partial_ends(&ls[0]).0, partial_ends(&ls[ls.len() - 1]).1)
+        }
+        _ => (false, false),
+    }
+}
+
+/// Translate `start..end`, reverse complemented unless `fwd`. Stops are
+/// translated as `*` and the first codon as an ordinary codon.
+pub fn translate_range(
+    seq: &Seq,
+    start: i64,
+    end: i64,
+    fwd: bool,
+    table: u8,
+) -> Result<String, String> {
+    let code = genetic_code::get(table)?;
+    let bases = range_bases(seq, start, end)?;
+    let bases = if fwd { bases } else { dna::revcomp(&bases) };
+    Ok(String::from_utf8(code.translate(&bases, false)).unwrap())
+}
+
+fn qualifier<'a>(f: &'a Feature, key: &str) -> Option<&'a str> {
+    f.qualifier_values(QualifierKey::from(key)).next()
+}
+
+fn feature_code(f: &Feature) -> Result<&'static GeneticCode, String> {
+    match qualifier(f, "transl_table") {
+        None => genetic_code::get(genetic_code::DEFAULT_TABLE),
+        Some(t) => t
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid /transl_table: {}", t))
+            .and_then(genetic_code::get),
+    }
+}
+
+pub fn translate_feature(seq: &Seq, f: &Feature) -> Result<FeatureTranslation, String> {
+    let code = feature_code(f)?;
+    let codon_start = match qualifier(f, "codon_start") {
+        None => 1,
+        Some(c) => match c.trim().parse::<usize>() {
+            Ok(c @ 1..=3) => c,
+            _ => return Err(format!("Invalid /codon_start: {}", c)),
+        },
+    };
+    let bases = location_bases(seq, &f.location)?;
+    let bases = bases.get(codon_start - 1..).unwrap_or(&[]);
+    let five_prime_partial = partial_ends(&f.location).0;
+    let mut protein = code.translate(bases, codon_start == 1 && !five_prime_partial);
+    if protein.last() == Some(&b'*') {
+        protein.pop();
+    }
+    let protein = String::from_utf8(protein).unwrap();
+    let expected: Option<String> =
+        qualifier(f, "translation").map(|t| t.chars().filter(|c| !c.is_whitespace()).collect());
+    let first_mismatch = expected.as_ref().and_then(|e| {
+        if *e == protein {
+            None
+        } else {
+            Some(
+                e.bytes()
+                    .zip(protein.bytes())
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| e.len().min(protein.len())),
+            )
+        }
+    });
+    Ok(FeatureTranslation {
+        protein,
+        table: code.id,
+        expected,
+        first_mismatch,
+    })
+}
+
+/// The indices of the features whose `/translation` differs from their
+/// actual translation, or which can't be translated
+pub fn check_translations(seq: &Seq) -> Vec<usize> {
+    seq.features
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| qualifier(f, "translation").is_some())
+        .filter(|(_, f)| match translate_feature(seq, f) {
+            Ok(t) => t.first_mismatch.is_some(),
+            Err(_) => true,
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::location;
+
+    fn feature(l: &str, qualifiers: &[(&str, &str)]) -> Feature {
+        Feature {
+            kind: feature_kind!("CDS"),
+            location: location::parse(l).unwrap(),
+            qualifiers: qualifiers
+                .iter()
+                .map(|&(k, v)| (QualifierKey::from(k), Some(v.to_string())))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_translate_range() {
+        let seq = Seq {
+            seq: b"ATGAAATAGccc".to_vec(),
+            topology: Topology::Circular,
+            ..Seq::empty()
+        };
+        assert_eq!(translate_range(&seq, 0, 9, true, 1).unwrap(), "MK*");
+        // CAT GGG across the origin on the reverse strand
+        assert_eq!(translate_range(&seq, 9, 15, false, 1).unwrap(), "HG");
+        assert!(translate_range(&seq, 9, 22, true, 1).is_err());
+        assert!(translate_range(&seq, 0, 9, true, 7).is_err());
+    }
+
+    #[test]
+    fn test_translate_feature() {
+        // exons ATG AA|A TGA, then CAT TTC on the reverse strand
+        let seq = Seq {
+            seq: b"ATGAAccAtgaGAAATG".to_vec(),
+            ..Seq::empty()
+        };
+        let f = feature("join(1..5,8..11)", &[("translation", "MK")]);
+        let t = translate_feature(&seq, &f).unwrap();
+        assert_eq!(t.protein, "MK");
+        assert_eq!(t.table, 1);
+        assert_eq!(t.first_mismatch, None);
+        // TGA is tryptophan in table 4
+        let f = feature(
+            "join(1..5,8..11)",
+            &[("transl_table", "4"), ("translation", "MKS")],
+        );
+        let t = translate_feature(&seq, &f).unwrap();
+        assert_eq!(t.protein, "MKW");
+        assert_eq!(t.first_mismatch, Some(2));
+        // CAT TTC, then ATT TC from its second base
+        let f = feature("complement(12..17)", &[]);
+        assert_eq!(translate_feature(&seq, &f).unwrap().protein, "HF");
+        let f = feature("complement(12..>17)", &[("codon_start", "2")]);
+        assert_eq!(translate_feature(&seq, &f).unwrap().protein, "I");
+        let f = feature("1..9", &[("codon_start", "4")]);
+        assert!(translate_feature(&seq, &f).is_err());
+        let seq = Seq {
+            features: vec![
+                feature("join(1..5,8..11)", &[("translation", "MK")]),
+                feature("1..6", &[]),
+                feature("1..6", &[("translation", "MR")]),
+            ],
+            ..seq
+        };
+        assert_eq!(check_translations(&seq), vec![2]);
+    }
+}